@@ -55,6 +55,7 @@ import {
 } from './core/workshop';
 import {
   liveApply,
+  liveApplyFile,
   liveAutoApplySet,
   liveAutoApplyUnset,
   liveBrowse,
@@ -77,6 +78,10 @@ import {
   liveViewData
 } from './core/live';
 
+// Version of the JSON payloads consumed by the UI (status, packs, history, logs, settings, jobs).
+// Bump on breaking shape changes; the Tauri backend refuses payloads it does not understand.
+export const CLI_OUTPUT_SCHEMA_VERSION = 1;
+
 function getCliVersion(): string {
   try {
     const pkgPath = join(__dirname, '..', 'package.json');
@@ -173,6 +178,7 @@ Commands:
                                           Download from provider and save into local library
   live apply <id> --monitor <name> [--quality auto|hd|4k]
                                           Apply live wallpaper by local library id
+  live apply-file <path> --monitor <name> Apply any local video file as live wallpaper
   live auto-apply set --monitor <name> --quality auto|hd|4k
   live auto-apply unset --monitor <name>
   live favorite <id> on|off
@@ -197,6 +203,9 @@ Commands:
   systemd-status                           Show systemd timer status
   rotate-now [--pack <name>]               Apply next wallpapers ignoring manual mode (for timers)
   mode <manual|rotate>                     Set mode persistently in state.json
+
+  --secrets-stdin                          Read --api-key / --steam-guard / set-api-key values from a JSON
+                                           object on stdin ({"apiKey": "...", "steamGuard": "..."})
 Examples:
   kitowall init --namespace kitowall --apply
   kitowall install-systemd --every 5m
@@ -221,6 +230,31 @@ function getOptionValue(args: string[], key: string): string | null {
   return args[idx + 1] ?? null;
 }
 
+// Secrets sent over stdin as a JSON object when `--secrets-stdin` is present, so
+// API keys and Steam Guard codes never show up in `ps`. Read once per process.
+let stdinSecrets: Record<string, unknown> | null = null;
+
+function stdinSecret(args: string[], key: string): string | null {
+  if (!args.includes('--secrets-stdin')) return null;
+  if (stdinSecrets === null) {
+    const raw = readFileSync(0, 'utf8').trim();
+    const parsed: unknown = raw ? JSON.parse(raw) : {};
+    if (!parsed || typeof parsed !== 'object' || Array.isArray(parsed)) {
+      throw new Error('--secrets-stdin expects a JSON object on stdin');
+    }
+    stdinSecrets = parsed as Record<string, unknown>;
+  }
+  const value = stdinSecrets[key];
+  return typeof value === 'string' ? value : null;
+}
+
+// With `--secrets-stdin` the value must come from stdin: falling back to argv
+// would pick up the flag itself (or a stray key) as the secret.
+function secretOptionValue(args: string[], key: string, secretKey: string): string | null {
+  if (args.includes('--secrets-stdin')) return stdinSecret(args, secretKey);
+  return getOptionValue(args, key);
+}
+
 function parseList(input?: string | null): string[] | undefined {
   if (!input) return undefined;
   return input.split(',').map(s => s.trim()).filter(Boolean);
@@ -389,8 +423,10 @@ async function main(): Promise<void> {
     if (action === 'config') {
       const sub = cleanOpt(args[2] ?? null);
       if (sub === 'set-api-key') {
-        const key = cleanOpt(args[3] ?? null);
-        if (!key) throw new Error('Usage: we config set-api-key <key>');
+        const fromStdin = args.includes('--secrets-stdin');
+        const key = cleanOpt(fromStdin ? stdinSecret(args, 'apiKey') : args[3] ?? null);
+        if (!key && fromStdin) throw new Error('--secrets-stdin: no "apiKey" in the JSON on stdin');
+        if (!key) throw new Error('Usage: we config set-api-key <key|--secrets-stdin>');
         setWorkshopApiKey(key);
         console.log(JSON.stringify({ok: true, updated: 'steamWebApiKey'}, null, 2));
         return;
@@ -453,7 +489,7 @@ async function main(): Promise<void> {
         targetDir: cleanOpt(getOptionValue(args, '--target-dir')),
         steamUser: cleanOpt(getOptionValue(args, '--steam-user')),
         steamPasswordEnv: cleanOpt(getOptionValue(args, '--steam-pass-env')),
-        steamGuardCode: cleanOpt(getOptionValue(args, '--steam-guard')),
+        steamGuardCode: cleanOpt(secretOptionValue(args, '--steam-guard', 'steamGuard')),
         useCoexistence: args.includes('--coexist')
       });
       console.log(JSON.stringify({ok: true, ...out}, null, 2));
@@ -472,7 +508,7 @@ async function main(): Promise<void> {
       const jobId = cleanOpt(args[2] ?? null);
       if (!jobId) throw new Error('Usage: we job <job_id>');
       const out = workshopGetJob(jobId);
-      console.log(JSON.stringify(out, null, 2));
+      console.log(JSON.stringify({schemaVersion: CLI_OUTPUT_SCHEMA_VERSION, ...out}, null, 2));
       return;
     }
 
@@ -481,7 +517,7 @@ async function main(): Promise<void> {
       const limit = limitRaw ? Number(limitRaw) : 40;
       if (!Number.isFinite(limit) || limit <= 0) throw new Error(`Invalid --limit value: ${limitRaw}`);
       const out = workshopListJobs(Math.floor(limit));
-      console.log(JSON.stringify(out, null, 2));
+      console.log(JSON.stringify({schemaVersion: CLI_OUTPUT_SCHEMA_VERSION, ...out}, null, 2));
       return;
     }
 
@@ -568,7 +604,7 @@ async function main(): Promise<void> {
 
   if (cmd === 'live') {
     const action = cleanOpt(args[1] ?? null);
-    if (!action) throw new Error('Usage: live <init|list|browse|search|resolve|preview|preview-clear|fetch|apply|auto-apply|favorite|remove|thumb|open|service-autostart|config|doctor> ...');
+    if (!action) throw new Error('Usage: live <init|list|browse|search|resolve|preview|preview-clear|fetch|apply|apply-file|auto-apply|favorite|remove|thumb|open|service-autostart|config|doctor> ...');
 
     if (action === 'init') {
       console.log(JSON.stringify(liveInit(), null, 2));
@@ -660,6 +696,15 @@ async function main(): Promise<void> {
       return;
     }
 
+    if (action === 'apply-file') {
+      const file = cleanOpt(args[2] ?? null);
+      const monitor = cleanOpt(getOptionValue(args, '--monitor'));
+      if (!file || !monitor) throw new Error('Usage: live apply-file <path> --monitor <name>');
+      const out = await liveApplyFile({file, monitor});
+      console.log(JSON.stringify(out, null, 2));
+      return;
+    }
+
     if (action === 'auto-apply') {
       const sub = cleanOpt(args[2] ?? null);
       if (sub === 'set') {
@@ -785,7 +830,7 @@ async function main(): Promise<void> {
     if (!action) throw new Error('Usage: pack <add|update|remove|list|show|subtheme|set-key> ...');
 
     if (action === 'list') {
-      console.log(JSON.stringify({packs: config.packs}, null, 2));
+      console.log(JSON.stringify({schemaVersion: CLI_OUTPUT_SCHEMA_VERSION, packs: config.packs}, null, 2));
       return;
     }
 
@@ -843,7 +888,7 @@ async function main(): Promise<void> {
       if (!name) throw new Error('Usage: pack set-key <name> [--api-key <k>] [--api-key-env <ENV>]');
       const pack = config.packs[name];
       if (!pack) throw new Error(`Pack not found: ${name}`);
-      const apiKey = cleanOpt(getOptionValue(args, '--api-key'));
+      const apiKey = cleanOpt(secretOptionValue(args, '--api-key', 'apiKey'));
       const apiKeyEnv = cleanOpt(getOptionValue(args, '--api-key-env'));
       if (!apiKey && !apiKeyEnv) throw new Error('Provide --api-key or --api-key-env');
       // @ts-expect-error dynamic field
@@ -868,7 +913,7 @@ async function main(): Promise<void> {
       const subthemes = parseList(getOptionValue(args, '--subthemes'));
       const paths = parseList(getOptionValue(args, '--paths'));
       const subreddits = parseList(getOptionValue(args, '--subreddits'));
-      const apiKey = cleanOpt(getOptionValue(args, '--api-key'));
+      const apiKey = cleanOpt(secretOptionValue(args, '--api-key', 'apiKey'));
       const apiKeyEnv = cleanOpt(getOptionValue(args, '--api-key-env'));
       const ratios = parseList(getOptionValue(args, '--ratios'));
       const categories = cleanOpt(getOptionValue(args, '--categories'));
@@ -1164,6 +1209,7 @@ async function main(): Promise<void> {
     }
     if (action === 'get') {
       console.log(JSON.stringify({
+        schemaVersion: CLI_OUTPUT_SCHEMA_VERSION,
         mode: state.mode,
         rotation_interval_seconds: config.rotation_interval_seconds,
         transition: config.transition
@@ -1329,7 +1375,7 @@ async function main(): Promise<void> {
       .sort((a, b) => b.timestamp - a.timestamp)
       .slice(0, Math.floor(limit))
       .map(e => ({...e, favorite: favorites.has(e.path)}));
-    console.log(JSON.stringify({entries: history}, null, 2));
+    console.log(JSON.stringify({schemaVersion: CLI_OUTPUT_SCHEMA_VERSION, entries: history}, null, 2));
     return;
   }
 
@@ -1349,7 +1395,7 @@ async function main(): Promise<void> {
     if (!Number.isFinite(limit) || limit <= 0) throw new Error(`Invalid --limit value: ${limitRaw}`);
     const level = levelRaw && ['info', 'warn', 'error'].includes(levelRaw) ? levelRaw as 'info' | 'warn' | 'error' : undefined;
     const entries = listSystemLogs({limit: Math.floor(limit), source, pack, level, q});
-    console.log(JSON.stringify({entries}, null, 2));
+    console.log(JSON.stringify({schemaVersion: CLI_OUTPUT_SCHEMA_VERSION, entries}, null, 2));
     return;
   }
 
@@ -1394,7 +1440,11 @@ async function main(): Promise<void> {
       rows.push({name, type: pack.type, count: candidates.length});
     }
     const poolEnabled = !!config.pool?.enabled;
-    console.log(JSON.stringify({packs: rows, pool: {enabled: poolEnabled, sources: config.pool?.sources ?? []}}, null, 2));
+    console.log(JSON.stringify({
+      schemaVersion: CLI_OUTPUT_SCHEMA_VERSION,
+      packs: rows,
+      pool: {enabled: poolEnabled, sources: config.pool?.sources ?? []}
+    }, null, 2));
     return;
   }
 
@@ -1558,6 +1608,7 @@ async function main(): Promise<void> {
     const outputs = await controller.getOutputs();
     console.log(JSON.stringify(
         {
+          schemaVersion: CLI_OUTPUT_SCHEMA_VERSION,
           mode: state.mode,
           pack: state.current_pack,
           outputs,
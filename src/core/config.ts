@@ -1,9 +1,9 @@
 // Config loading/saving and defaults.
-import os from 'os';
 import path from 'path';
 import fs from 'fs';
 import {readJson, writeJson} from '../utils/fs';
 import {assertValidConfig, validateConfig} from './configValidator';
+import {kitowallCacheDir, kitowallConfigFile} from './paths';
 
 export type PackType =
     | 'local'
@@ -20,7 +20,7 @@ function deepEqual(a: unknown, b: unknown): boolean {
 }
 
 export function getConfigPath(): string {
-  return path.join(os.homedir(), '.config', 'kitowall', 'config.json');
+  return kitowallConfigFile();
 }
 
 function ensureConfigDir(): void {
@@ -220,7 +220,7 @@ export function defaultConfig(): Config {
       avoidSameTickDuplicates: true
     },
     cache: {
-      dir: '~/.cache/kitowall',
+      dir: kitowallCacheDir(),
       downloadDir: '~/Pictures/Wallpapers',
       maxMB: 2048,
       defaultTtlSec: 604800
@@ -1,14 +1,14 @@
 // Favorites persistence for wallpapers.
-import os from 'os';
 import path from 'path';
 import {readJson, writeJson} from '../utils/fs';
+import {kitowallStateDir} from './paths';
 
 export interface FavoritesState {
   favorites: string[];
 }
 
 export function getFavoritesPath(): string {
-  return path.join(os.homedir(), '.local', 'state', 'kitowall', 'favorites.json');
+  return path.join(kitowallStateDir(), 'favorites.json');
 }
 
 export function loadFavorites(): FavoritesState {
@@ -1,8 +1,8 @@
 // History persistence for applied wallpapers.
-import os from 'os';
 import path from 'path';
 import fs from 'fs';
 import {readJson, writeJson} from '../utils/fs';
+import {kitowallStateDir} from './paths';
 
 export interface HistoryEntry {
   timestamp: number;
@@ -16,7 +16,7 @@ export interface HistoryState {
 }
 
 export function getHistoryPath(): string {
-  return path.join(os.homedir(), '.local', 'state', 'kitowall', 'history.json');
+  return path.join(kitowallStateDir(), 'history.json');
 }
 
 export function loadHistory(): HistoryState {
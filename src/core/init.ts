@@ -126,6 +126,12 @@ export async function initKitowall(opts: {
         '/bin'
     ].join(':');
 
+    // Units run outside the login shell, so carry over where config/state live.
+    const pathOverrides = ['KITOWALL_CONFIG', 'XDG_CONFIG_HOME', 'XDG_STATE_HOME']
+        .filter(k => process.env[k]?.trim())
+        .map(k => `Environment=${k}=${process.env[k]!.trim()}\n`)
+        .join('');
+
     // Resolve WAYLAND_DISPLAY at runtime for every service start. This avoids
     // stale values across relogin (e.g. wayland-0 vs wayland-1).
     const waylandBootstrap =
@@ -168,7 +174,7 @@ Requires=swww-daemon@${ns}.service
 Type=oneshot
 Environment=PATH=${pathEnv}
 Environment=XDG_RUNTIME_DIR=${xdgRuntimeDir}
-ExecStart=${nextExec}
+${pathOverrides}ExecStart=${nextExec}
 `.trimStart();
 
     writeFileSync(join(userDir, 'kitowall-next.service'), kitowallNextService, 'utf8');
@@ -187,7 +193,7 @@ Wants=graphical-session.target
 Type=simple
 Environment=PATH=${pathEnv}
 Environment=XDG_RUNTIME_DIR=${xdgRuntimeDir}
-ExecStart=${watchExec}
+${pathOverrides}ExecStart=${watchExec}
 Restart=on-failure
 RestartSec=1
 
@@ -211,7 +217,7 @@ Wants=graphical-session.target
 Type=oneshot
 Environment=PATH=${pathEnv}
 Environment=XDG_RUNTIME_DIR=${xdgRuntimeDir}
-ExecStart=${loginApplyExec}
+${pathOverrides}ExecStart=${loginApplyExec}
 
 [Install]
 WantedBy=default.target
@@ -1922,11 +1922,42 @@ export async function liveApply(opts: {
   }
 
   const bin = resolveRendercoreBin(index);
+  const setVideoArgs = await playVideo(bin, index, monitor, item.file_path);
 
+  withLiveLock(() => {
+    const current = readIndex();
+    const updatedItems = current.items.map(v => (v.id === item.id ? {...v, last_applied_at: nowUnix()} : v));
+    const perMonitor = {...current.per_monitor};
+    const currentMon = perMonitor[monitor] || {
+      auto_apply: false,
+      preferred_quality: 'auto' as LiveQuality,
+      last_applied_id: null
+    };
+    perMonitor[monitor] = {
+      ...currentMon,
+      last_applied_id: item.id
+    };
+    writeIndexAtomic({...current, items: updatedItems, per_monitor: perMonitor});
+    return true;
+  });
+
+  return {
+    ok: true,
+    id: item.id,
+    monitor,
+    runner: index.runner.mode,
+    command: bin,
+    args: setVideoArgs
+  };
+}
+
+// Hands a video file to the rendercore runner for one monitor and makes sure the
+// runner keeps it after a session restart. Returns the set-video args.
+async function playVideo(bin: string, index: LiveIndex, monitor: string, filePath: string): Promise<string[]> {
   const setVideoArgs = [
     'set-video',
     '--monitor', monitor,
-    '--video', item.file_path
+    '--video', filePath
   ];
   await run(bin, setVideoArgs, {timeoutMs: 120000});
 
@@ -1950,27 +1981,30 @@ export async function liveApply(opts: {
     await runSystemctlUser(['enable', 'kitsune-rendercore.service']);
     await runSystemctlUser(['start', 'kitsune-rendercore.service']);
   }
+  return setVideoArgs;
+}
 
-  withLiveLock(() => {
-    const current = readIndex();
-    const updatedItems = current.items.map(v => (v.id === item.id ? {...v, last_applied_at: nowUnix()} : v));
-    const perMonitor = {...current.per_monitor};
-    const currentMon = perMonitor[monitor] || {
-      auto_apply: false,
-      preferred_quality: 'auto' as LiveQuality,
-      last_applied_id: null
-    };
-    perMonitor[monitor] = {
-      ...currentMon,
-      last_applied_id: item.id
-    };
-    writeIndexAtomic({...current, items: updatedItems, per_monitor: perMonitor});
-    return true;
-  });
+// Applies any video file, e.g. a clip from a local pack, without it being in the
+// live index.
+export async function liveApplyFile(opts: {
+  file: string;
+  monitor: string;
+}): Promise<{ok: true; file: string; monitor: string; runner: RunnerMode; command: string; args: string[]}> {
+  const file = clean(opts.file);
+  const monitor = clean(opts.monitor);
+  if (!file) throw new Error('file is required');
+  if (!monitor) throw new Error('monitor is required');
+  const filePath = path.resolve(file);
+  if (!fs.existsSync(filePath) || !fs.statSync(filePath).isFile()) {
+    throw new Error(`Video file not found: ${filePath}`);
+  }
 
+  const index = readIndex();
+  const bin = resolveRendercoreBin(index);
+  const setVideoArgs = await playVideo(bin, index, monitor, filePath);
   return {
     ok: true,
-    id: item.id,
+    file: filePath,
     monitor,
     runner: index.runner.mode,
     command: bin,
@@ -1,8 +1,8 @@
 // Persistent system logs for requests/downloads and diagnostics.
 import fs from 'fs';
 import path from 'path';
-import os from 'os';
 import {ensureDir} from '../utils/fs';
+import {kitowallStateDir} from './paths';
 
 export type LogLevel = 'info' | 'warn' | 'error';
 
@@ -18,7 +18,7 @@ export interface SystemLogEntry {
   meta?: Record<string, unknown>;
 }
 
-const LOG_DIR = path.join(os.homedir(), '.local', 'state', 'kitowall');
+const LOG_DIR = kitowallStateDir();
 const LOG_FILE = path.join(LOG_DIR, 'logs.jsonl');
 const MAX_LINES = 5000;
 
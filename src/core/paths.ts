@@ -0,0 +1,43 @@
+// XDG base directories used by kitowall. Keep in sync with
+// `ui/src-tauri/src/paths.rs`, which must find the same files.
+import os from 'os';
+import path from 'path';
+import {expandTilde} from '../utils/fs';
+
+function xdgDir(envVar: string, fallback: string[]): string {
+  const value = process.env[envVar]?.trim();
+  // The spec says relative values are invalid and must be ignored.
+  if (value && path.isAbsolute(value)) return value;
+  return path.join(os.homedir(), ...fallback);
+}
+
+export function configHome(): string {
+  return xdgDir('XDG_CONFIG_HOME', ['.config']);
+}
+
+export function stateHome(): string {
+  return xdgDir('XDG_STATE_HOME', ['.local', 'state']);
+}
+
+export function cacheHome(): string {
+  return xdgDir('XDG_CACHE_HOME', ['.cache']);
+}
+
+export function kitowallConfigDir(): string {
+  return path.join(configHome(), 'kitowall');
+}
+
+export function kitowallStateDir(): string {
+  return path.join(stateHome(), 'kitowall');
+}
+
+export function kitowallCacheDir(): string {
+  return path.join(cacheHome(), 'kitowall');
+}
+
+/** `KITOWALL_CONFIG` points at an alternative config.json. */
+export function kitowallConfigFile(): string {
+  const override = process.env.KITOWALL_CONFIG?.trim();
+  if (override) return path.resolve(expandTilde(override));
+  return path.join(kitowallConfigDir(), 'config.json');
+}
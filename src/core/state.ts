@@ -1,8 +1,8 @@
 // Runtime state persistence.
-import os from 'os';
 import path from 'path';
 import fs from 'fs';
 import {readJson, writeJson} from '../utils/fs';
+import {kitowallStateDir} from './paths';
 
 export const STATE_SCHEMA_VERSION = 1;
 
@@ -105,7 +105,7 @@ export function commitSelection(state: State, output: string, pathStr: string, n
 }
 
 export function getStatePath(): string {
-  return path.join(os.homedir(), '.local', 'state', 'kitowall', 'state.json');
+  return path.join(kitowallStateDir(), 'state.json');
 }
 
 function ensureStateDir(): void {
@@ -7,6 +7,7 @@ import {ensureDir, writeJson} from '../utils/fs';
 import {fetchWithRetry} from '../utils/net';
 import {appendSystemLog} from './logs';
 import {run} from '../utils/exec';
+import {kitowallConfigDir} from './paths';
 
 const WE_APP_ID = 431960;
 const SEARCH_TTL_MS = 10 * 60 * 1000;
@@ -168,7 +169,7 @@ function ensureWePaths(paths: WorkshopPaths): void {
 }
 
 function getWeConfigPath(): string {
-  return path.join(os.homedir(), '.config', 'kitowall', 'we.json');
+  return path.join(kitowallConfigDir(), 'we.json');
 }
 
 function readWeConfig(): WeConfig {
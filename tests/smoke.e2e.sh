@@ -35,6 +35,7 @@ echo "==> settings get/set"
 ${CLI} settings set --mode rotate --rotation-interval-sec 600 --transition-type center --transition-fps 60 --transition-duration 0.7 >/dev/null
 SETTINGS_OUT="$(${CLI} settings get)"
 echo "${SETTINGS_OUT}" | rg -q '"mode": "rotate"'
+echo "${SETTINGS_OUT}" | rg -q '"schemaVersion": 1'
 
 echo "==> favorites/history/logs"
 ${CLI} favorite add /tmp/smoke.jpg >/dev/null
@@ -0,0 +1,178 @@
+// Record of every host command the UI ran: a bounded in-memory ring, plus a
+// JSONL file when KITOWALL_UI_AUDIT_LOG is set. Secrets never reach either.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const RING_CAPACITY: usize = 500;
+const STDERR_LIMIT: usize = 2048;
+const REDACTED: &str = "<redacted>";
+
+/// Flags whose following argument (or `=value`) is a secret.
+const SECRET_FLAGS: [&str; 7] = [
+    "--api-key",
+    "--steam-guard",
+    "--steam-pass",
+    "--steam-password",
+    "--password",
+    "--token",
+    "--secret",
+];
+/// Subcommands whose next positional argument is a secret.
+const SECRET_SUBCOMMANDS: [&str; 1] = ["set-api-key"];
+/// Name fragments marking an environment variable as secret (`--env=NAME=value`).
+const SECRET_ENV_HINTS: [&str; 5] = ["KEY", "TOKEN", "PASS", "SECRET", "GUARD"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub request_id: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub exit_code: Option<i32>,
+    /// `ok`, `failed`, `timeout`, `cancelled` or `error`.
+    pub outcome: &'static str,
+    pub stderr: String,
+}
+
+/// An invocation that has started but not finished yet.
+pub struct PendingCall {
+    request_id: String,
+    program: String,
+    args: Vec<String>,
+    started_at: u64,
+    started: Instant,
+}
+
+impl PendingCall {
+    pub fn start(request_id: &str, command: &Command) -> Self {
+        let args: Vec<String> = command.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+        Self {
+            request_id: request_id.to_string(),
+            program: command.get_program().to_string_lossy().to_string(),
+            args: redact_args(&args),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn finish(self, outcome: &'static str, exit_code: Option<i32>, stderr: &str) -> AuditEntry {
+        AuditEntry {
+            request_id: self.request_id,
+            program: self.program,
+            args: self.args,
+            started_at: self.started_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            exit_code,
+            outcome,
+            stderr: truncate(crate::error::scrub(stderr).trim(), STDERR_LIMIT),
+        }
+    }
+}
+
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for arg in args {
+        if hide_next {
+            out.push(REDACTED.to_string());
+            hide_next = false;
+            continue;
+        }
+        if let Some(assignment) = arg.strip_prefix("--env=") {
+            let name = assignment.split('=').next().unwrap_or_default();
+            if SECRET_ENV_HINTS.iter().any(|h| name.to_ascii_uppercase().contains(h)) {
+                out.push(format!("--env={name}={REDACTED}"));
+                continue;
+            }
+        }
+        if let Some((flag, _)) = arg.split_once('=') {
+            if SECRET_FLAGS.contains(&flag) {
+                out.push(format!("{flag}={REDACTED}"));
+                continue;
+            }
+        }
+        hide_next = SECRET_FLAGS.contains(&arg.as_str()) || SECRET_SUBCOMMANDS.contains(&arg.as_str());
+        out.push(arg.clone());
+    }
+    out
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.len() <= limit {
+        return text.to_string();
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+pub struct AuditLog {
+    ring: Mutex<VecDeque<AuditEntry>>,
+    file: Option<PathBuf>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        let file = std::env::var("KITOWALL_UI_AUDIT_LOG")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+        Self {
+            ring: Mutex::new(VecDeque::with_capacity(RING_CAPACITY)),
+            file,
+        }
+    }
+
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.file.as_ref()
+    }
+
+    pub fn record(&self, entry: AuditEntry) {
+        if let Some(path) = &self.file {
+            // Best effort: a broken audit file must not fail the command itself.
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(f, "{line}");
+            }
+        }
+        if let Ok(mut ring) = self.ring.lock() {
+            if ring.len() == RING_CAPACITY {
+                ring.pop_front();
+            }
+            ring.push_back(entry);
+        }
+    }
+
+    /// Most recent entries first.
+    pub fn entries(&self, limit: usize) -> Vec<AuditEntry> {
+        let Ok(ring) = self.ring.lock() else {
+            return vec![];
+        };
+        ring.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn capacity(&self) -> usize {
+        RING_CAPACITY
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,368 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::sync::oneshot;
+
+use crate::audit::{self, AuditLog, PendingCall};
+use crate::cache::QueryCache;
+use crate::error::UiError;
+use crate::runner::{CliRunner, HostRunner};
+use crate::tools::ToolCache;
+
+type Json = Value;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+/// Budget for hydrate, workshop downloads and host bootstrap; these routinely run for minutes.
+pub const LONG_RUNNING_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Default, Clone)]
+pub struct CallOptions {
+    pub request_id: Option<String>,
+    pub timeout: Option<Duration>,
+    /// Written to the child's stdin, then closed. Used for secrets, so it is never logged.
+    pub stdin: Option<String>,
+}
+
+impl CallOptions {
+    pub fn from_ui(request_id: Option<String>, timeout_ms: Option<u64>) -> Self {
+        Self {
+            request_id,
+            timeout: timeout_ms.map(Duration::from_millis),
+            stdin: None,
+        }
+    }
+
+    pub fn with_stdin(mut self, input: String) -> Self {
+        self.stdin = Some(input);
+        self
+    }
+
+    pub fn long_running(mut self) -> Self {
+        if self.timeout.is_none() {
+            self.timeout = Some(LONG_RUNNING_TIMEOUT);
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Receives every output line as it is read: `(request_id, stream, line)`.
+pub type LineSink<'a> = &'a (dyn Fn(&str, OutputStream, &str) + Sync);
+
+#[derive(Debug)]
+pub struct CliOutput {
+    pub request_id: String,
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug)]
+pub enum ExecError {
+    Spawn(std::io::Error),
+    Io(std::io::Error),
+    TimedOut { request_id: String, after: Duration },
+    Cancelled { request_id: String },
+}
+
+impl From<ExecError> for UiError {
+    fn from(err: ExecError) -> Self {
+        match err {
+            ExecError::Spawn(e) | ExecError::Io(e) => UiError::Host(e.to_string()),
+            ExecError::TimedOut { request_id, after } => {
+                UiError::Timeout(format!("{request_id} after {}s", after.as_secs()))
+            }
+            ExecError::Cancelled { request_id } => UiError::Cancelled(request_id),
+        }
+    }
+}
+
+struct InflightCall {
+    program: String,
+    args: Vec<String>,
+    started: Instant,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+/// Async executor for host CLI calls. Every call gets a request id that can be
+/// passed to `cancel`, and is killed once its timeout elapses.
+pub struct CliBridge {
+    next_id: AtomicU64,
+    default_timeout: Duration,
+    inflight: Mutex<HashMap<String, InflightCall>>,
+    pub tools: ToolCache,
+    pub audit: AuditLog,
+    pub cache: QueryCache,
+    config_lock: tokio::sync::Mutex<()>,
+    runner: Box<dyn CliRunner>,
+}
+
+struct InflightGuard<'a> {
+    bridge: &'a CliBridge,
+    request_id: String,
+}
+
+impl Drop for InflightGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut map) = self.bridge.inflight.lock() {
+            map.remove(&self.request_id);
+        }
+    }
+}
+
+enum Outcome {
+    Exited(std::io::Result<ExitStatus>, std::io::Result<String>, std::io::Result<String>),
+    TimedOut,
+    Cancelled,
+}
+
+async fn read_pipe<R: AsyncRead + Unpin>(
+    pipe: Option<R>,
+    stream: OutputStream,
+    request_id: &str,
+    sink: Option<LineSink<'_>>,
+) -> std::io::Result<String> {
+    let mut collected = String::new();
+    let Some(p) = pipe else {
+        return Ok(collected);
+    };
+    let mut reader = BufReader::new(p);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).await? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf);
+        if let Some(emit) = sink {
+            emit(request_id, stream, line.trim_end_matches(['\r', '\n']));
+        }
+        collected.push_str(&line);
+    }
+    Ok(collected)
+}
+
+impl Default for CliBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CliBridge {
+    pub fn new() -> Self {
+        Self::with_runner(HostRunner)
+    }
+
+    /// Bridge whose kitowall calls go through `runner` instead of the host CLI.
+    pub fn with_runner(runner: impl CliRunner + 'static) -> Self {
+        // Optional override for slow hosts; per-call timeouts still take precedence.
+        let secs = std::env::var("KITOWALL_UI_TIMEOUT_SEC")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        Self {
+            next_id: AtomicU64::new(1),
+            default_timeout: Duration::from_secs(secs),
+            inflight: Mutex::new(HashMap::new()),
+            tools: ToolCache::default(),
+            audit: AuditLog::new(),
+            cache: QueryCache::default(),
+            config_lock: tokio::sync::Mutex::new(()),
+            runner: Box::new(runner),
+        }
+    }
+
+    pub fn runner(&self) -> &dyn CliRunner {
+        self.runner.as_ref()
+    }
+
+    /// Serializes the UI's own commands that rewrite config.json. Hold the guard
+    /// for the whole read-decide-write sequence; read-only calls never take it.
+    /// This is an in-process lock: the CLI doesn't honor it, so a `kitowall`
+    /// run from a terminal can still interleave. The timer's `next` only reads
+    /// config.json (it writes state.json), so it can't race these writes.
+    pub async fn lock_config(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.config_lock.lock().await
+    }
+
+    fn next_request_id(&self) -> String {
+        format!("kw-{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn register(&self, command: &Command, request_id: &str) -> Result<oneshot::Receiver<()>, ExecError> {
+        let (tx, rx) = oneshot::channel();
+        let mut map = self
+            .inflight
+            .lock()
+            .map_err(|_| ExecError::Io(std::io::Error::other("inflight registry lock poisoned")))?;
+        if map.contains_key(request_id) {
+            return Err(ExecError::Io(std::io::Error::other(format!(
+                "request id already in use: {request_id}"
+            ))));
+        }
+        map.insert(
+            request_id.to_string(),
+            InflightCall {
+                program: command.get_program().to_string_lossy().to_string(),
+                args: audit::redact_args(
+                    &command.get_args().map(|a| a.to_string_lossy().to_string()).collect::<Vec<_>>(),
+                ),
+                started: Instant::now(),
+                cancel: Some(tx),
+            },
+        );
+        Ok(rx)
+    }
+
+    /// Runs `command` to completion, honoring the call's timeout and cancellation.
+    pub async fn exec(&self, command: Command, opts: CallOptions) -> Result<CliOutput, ExecError> {
+        self.run(command, opts, None).await
+    }
+
+    /// Same as `exec`, but hands each stdout/stderr line to `sink` while the process runs.
+    pub async fn exec_streaming(
+        &self,
+        command: Command,
+        opts: CallOptions,
+        sink: LineSink<'_>,
+    ) -> Result<CliOutput, ExecError> {
+        self.run(command, opts, Some(sink)).await
+    }
+
+    async fn run(&self, command: Command, opts: CallOptions, sink: Option<LineSink<'_>>) -> Result<CliOutput, ExecError> {
+        let request_id = opts
+            .request_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| self.next_request_id());
+        let timeout = opts.timeout.unwrap_or(self.default_timeout);
+
+        let call = PendingCall::start(&request_id, &command);
+        let result = self.run_child(command, request_id, timeout, opts.stdin, sink).await;
+        self.audit.record(match &result {
+            Ok(out) => {
+                let outcome = if out.status.success() { "ok" } else { "failed" };
+                call.finish(outcome, out.status.code(), &out.stderr)
+            }
+            Err(ExecError::TimedOut { .. }) => call.finish("timeout", None, ""),
+            Err(ExecError::Cancelled { .. }) => call.finish("cancelled", None, ""),
+            Err(ExecError::Spawn(e) | ExecError::Io(e)) => call.finish("error", None, &e.to_string()),
+        });
+        result
+    }
+
+    async fn run_child(
+        &self,
+        command: Command,
+        request_id: String,
+        timeout: Duration,
+        stdin: Option<String>,
+        sink: Option<LineSink<'_>>,
+    ) -> Result<CliOutput, ExecError> {
+        let cancel_rx = self.register(&command, &request_id)?;
+        let _guard = InflightGuard { bridge: self, request_id: request_id.clone() };
+
+        let mut command = tokio::process::Command::from(command);
+        command
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command.spawn().map_err(ExecError::Spawn)?;
+        let stdin_pipe = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        // Fed inside the timed section, so a child that never reads stdin still
+        // times out. Dropping the pipe afterwards signals EOF; a child that exits
+        // without reading reports its own error, so a broken pipe is ignored.
+        let feed_stdin = async {
+            if let (Some(input), Some(mut pipe)) = (stdin, stdin_pipe) {
+                let _ = pipe.write_all(input.as_bytes()).await;
+            }
+        };
+
+        let outcome = tokio::select! {
+            (status, out, err) = async {
+                tokio::join!(
+                    async {
+                        feed_stdin.await;
+                        child.wait().await
+                    },
+                    read_pipe(stdout, OutputStream::Stdout, &request_id, sink),
+                    read_pipe(stderr, OutputStream::Stderr, &request_id, sink)
+                )
+            } => {
+                Outcome::Exited(status, out, err)
+            }
+            _ = tokio::time::sleep(timeout) => Outcome::TimedOut,
+            Ok(()) = cancel_rx => Outcome::Cancelled,
+        };
+
+        match outcome {
+            Outcome::Exited(status, out, err) => Ok(CliOutput {
+                request_id,
+                status: status.map_err(ExecError::Io)?,
+                stdout: out.map_err(ExecError::Io)?,
+                stderr: err.map_err(ExecError::Io)?,
+            }),
+            Outcome::TimedOut => {
+                let _ = child.kill().await;
+                Err(ExecError::TimedOut { request_id, after: timeout })
+            }
+            Outcome::Cancelled => {
+                let _ = child.kill().await;
+                Err(ExecError::Cancelled { request_id })
+            }
+        }
+    }
+
+    /// Signals the in-flight call to kill its child process. Returns false for unknown ids.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        let Ok(mut map) = self.inflight.lock() else {
+            return false;
+        };
+        match map.get_mut(request_id).and_then(|call| call.cancel.take()) {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn inflight(&self) -> Vec<Json> {
+        let Ok(map) = self.inflight.lock() else {
+            return vec![];
+        };
+        let mut items: Vec<(&String, &InflightCall)> = map.iter().collect();
+        items.sort_by_key(|(_, call)| call.started);
+        items
+            .into_iter()
+            .map(|(id, call)| {
+                serde_json::json!({
+                    "requestId": id,
+                    "program": call.program,
+                    "args": call.args,
+                    "elapsedMs": call.started.elapsed().as_millis() as u64,
+                    "cancelling": call.cancel.is_none()
+                })
+            })
+            .collect()
+    }
+}
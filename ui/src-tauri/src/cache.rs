@@ -0,0 +1,132 @@
+// Short-lived cache for read-only CLI queries. Any other kitowall invocation is
+// treated as a mutation and drops every entry, so a stale read can only come
+// from changes made outside the UI, and only for up to `TTL`.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+type Json = Value;
+
+const TTL: Duration = Duration::from_secs(5);
+
+/// Queries worth caching: config views the UI re-reads constantly. Job, log and
+/// history queries are read-only too but are polled for fresh data. Settings
+/// are read natively from config.json (`config::load`), so `settings get` no
+/// longer goes through the CLI and is deliberately not listed.
+const CACHEABLE: [&[&str]; 5] = [
+    &["status"],
+    &["list-packs"],
+    &["pack", "list"],
+    &["pack", "show"],
+    &["favorites"],
+];
+
+/// argv prefixes that never write config, state or the filesystem. Anything
+/// not listed here invalidates the cache, so new commands are safe by default.
+const READ_ONLY: [&[&str]; 24] = [
+    &["--version"],
+    &["status"],
+    &["list-packs"],
+    &["pack", "list"],
+    &["pack", "show"],
+    &["settings", "get"],
+    &["favorites"],
+    &["outputs"],
+    &["check"],
+    &["health"],
+    &["doctor"],
+    &["systemd-status"],
+    &["we", "job"],
+    &["we", "jobs"],
+    &["we", "library"],
+    &["we", "search"],
+    &["we", "details"],
+    &["we", "active"],
+    &["we", "app-status"],
+    &["we", "coexist", "status"],
+    &["we", "config", "get-steam-roots"],
+    &["live", "list"],
+    &["history"],
+    &["logs"],
+];
+
+fn has_prefix(args: &[String], prefix: &[&str]) -> bool {
+    prefix.len() <= args.len() && prefix.iter().zip(args).all(|(p, a)| p == a)
+}
+
+pub fn is_read_only(args: &[String]) -> bool {
+    // `history clear` / `logs clear` share their prefix with the read commands.
+    if matches!(args, [cmd, sub, ..] if (cmd == "history" || cmd == "logs") && sub == "clear") {
+        return false;
+    }
+    READ_ONLY.iter().any(|prefix| has_prefix(args, prefix))
+}
+
+pub fn is_cacheable(args: &[String]) -> bool {
+    CACHEABLE.iter().any(|prefix| has_prefix(args, prefix))
+}
+
+type Notifier = Box<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Default)]
+pub struct QueryCache {
+    entries: Mutex<HashMap<Vec<String>, (Instant, Json)>>,
+    /// Bumped on every invalidation so a query that raced a mutation is not stored.
+    generation: AtomicU64,
+    notifier: OnceLock<Notifier>,
+}
+
+impl QueryCache {
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, args: &[String]) -> Option<Json> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(args) {
+            Some((at, json)) if at.elapsed() < TTL => Some(json.clone()),
+            Some(_) => {
+                entries.remove(args);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `json` unless the cache was invalidated since `generation` was read.
+    pub fn put(&self, generation: u64, args: Vec<String>, json: Json) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if self.generation() == generation {
+            entries.insert(args, (Instant::now(), json));
+        }
+    }
+
+    /// Drops every entry and tells the UI (`reason` is the mutating command).
+    pub fn invalidate(&self, reason: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            entries.clear();
+        }
+        if let Some(notify) = self.notifier.get() {
+            notify(reason);
+        }
+    }
+
+    /// Drops entries under `prefix` without notifying; for changes the UI
+    /// already hears about through another event.
+    pub fn forget(&self, prefix: &[&str]) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|args, _| !has_prefix(args, prefix));
+        }
+    }
+
+    /// Installs the callback run after each invalidation. Only the first call wins.
+    pub fn on_invalidate(&self, notify: impl Fn(&str) + Send + Sync + 'static) {
+        let _ = self.notifier.set(Box::new(notify));
+    }
+}
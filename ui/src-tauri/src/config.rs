@@ -0,0 +1,358 @@
+// Typed model of kitowall's config.json, mirroring `src/core/config.ts`.
+// The UI reads it directly for views that need no CLI round-trip; writes still
+// go through kitowall so validation and migrations live in one place.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::error::UiError;
+use crate::models::{PoolSource, SettingsPayload, TransitionSettings};
+use crate::state::RuntimeState;
+
+/// Config schema version this UI build understands; keep in sync with
+/// `CONFIG_SCHEMA_VERSION` in `src/core/config.ts`.
+pub const CONFIG_SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Manual,
+    Rotate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SelectionConfig {
+    pub per_output_cooldown: u64,
+    pub global_cooldown: u64,
+    pub avoid_same_tick_duplicates: bool,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            per_output_cooldown: 10,
+            global_cooldown: 20,
+            avoid_same_tick_duplicates: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheConfig {
+    pub dir: String,
+    pub download_dir: String,
+    #[serde(rename = "maxMB")]
+    pub max_mb: u64,
+    pub default_ttl_sec: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            // `defaultConfig` in the CLI uses the XDG cache dir as well.
+            dir: crate::paths::cache_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_else(|_| "~/.cache/kitowall".to_string()),
+            download_dir: "~/Pictures/Wallpapers".to_string(),
+            max_mb: 2048,
+            default_ttl_sec: 604800,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub sources: Vec<PoolSource>,
+    /// `path`, `hash` or `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedupe: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalPack {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WallhavenPack {
+    pub api_key: Option<String>,
+    pub api_key_env: Option<String>,
+    pub keyword: Option<String>,
+    #[serde(deserialize_with = "string_list")]
+    pub subthemes: Vec<String>,
+    pub categories: Option<String>,
+    pub purity: Option<String>,
+    pub allow_sfw: Option<bool>,
+    pub allow_sketchy: Option<bool>,
+    pub allow_nsfw: Option<bool>,
+    pub category_general: Option<bool>,
+    pub category_anime: Option<bool>,
+    pub category_people: Option<bool>,
+    #[serde(deserialize_with = "string_list")]
+    pub ratios: Vec<String>,
+    pub colors: Option<String>,
+    pub atleast: Option<String>,
+    pub sorting: Option<String>,
+    pub ai_art: Option<bool>,
+    pub ttl_sec: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedditPack {
+    #[serde(deserialize_with = "string_list")]
+    pub subreddits: Vec<String>,
+    #[serde(deserialize_with = "string_list")]
+    pub subthemes: Vec<String>,
+    pub allow_sfw: Option<bool>,
+    pub min_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub ratio_w: Option<f64>,
+    pub ratio_h: Option<f64>,
+    pub sort: Option<String>,
+    pub time: Option<String>,
+    pub ttl_sec: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UnsplashPack {
+    pub api_key: Option<String>,
+    pub api_key_env: Option<String>,
+    pub query: Option<String>,
+    #[serde(deserialize_with = "string_list")]
+    pub subthemes: Vec<String>,
+    pub topics: Option<String>,
+    pub collections: Option<String>,
+    pub username: Option<String>,
+    pub orientation: Option<String>,
+    pub content_filter: Option<String>,
+    pub image_width: Option<f64>,
+    pub image_height: Option<f64>,
+    pub image_fit: Option<String>,
+    pub image_quality: Option<f64>,
+    pub ttl_sec: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GenericJsonPack {
+    pub endpoint: Option<String>,
+    pub image_path: Option<String>,
+    pub image_prefix: Option<String>,
+    pub candidate_limit: Option<f64>,
+    pub post_path: Option<String>,
+    pub post_prefix: Option<String>,
+    pub author_name_path: Option<String>,
+    pub author_url_path: Option<String>,
+    pub author_url_prefix: Option<String>,
+    pub domain: Option<String>,
+    pub ttl_sec: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StaticUrlPack {
+    pub url: Option<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub urls: Vec<String>,
+    pub author_name: Option<String>,
+    pub author_url: Option<String>,
+    pub domain: Option<String>,
+    pub post_url: Option<String>,
+    pub different_images: Option<bool>,
+    pub count: Option<f64>,
+    pub ttl_sec: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PackConfig {
+    Local(LocalPack),
+    Wallhaven(WallhavenPack),
+    Reddit(RedditPack),
+    Unsplash(UnsplashPack),
+    GenericJson(GenericJsonPack),
+    StaticUrl(StaticUrlPack),
+}
+
+impl PackConfig {
+    /// `(apiKey, apiKeyEnv)` for the sources that take a key.
+    pub fn api_key(&self) -> Option<(Option<&str>, Option<&str>)> {
+        match self {
+            PackConfig::Wallhaven(p) => Some((p.api_key.as_deref(), p.api_key_env.as_deref())),
+            PackConfig::Unsplash(p) => Some((p.api_key.as_deref(), p.api_key_env.as_deref())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u64,
+    pub mode: Mode,
+    pub rotation_interval_seconds: u64,
+    pub transition: TransitionSettings,
+    pub selection: SelectionConfig,
+    pub cache: CacheConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<PoolConfig>,
+    #[serde(deserialize_with = "valid_packs")]
+    pub packs: BTreeMap<String, PackConfig>,
+    /// One line per pack that `valid_packs` skipped, shown by the UI.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            mode: Mode::Manual,
+            rotation_interval_seconds: 1800,
+            transition: TransitionSettings {
+                kind: "center".to_string(),
+                fps: 60.0,
+                duration: 0.7,
+                angle: None,
+                pos: None,
+            },
+            selection: SelectionConfig::default(),
+            cache: CacheConfig::default(),
+            pool: None,
+            packs: BTreeMap::new(),
+            warnings: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// Where `kitowall` downloads remote packs (`cache.downloadDir`).
+    pub fn download_root(&self) -> Result<PathBuf, UiError> {
+        crate::expand_tilde_path(&self.cache.download_dir)
+    }
+
+    /// Every folder of a `local` pack, paired with the pack name.
+    pub fn local_pack_roots(&self) -> Result<Vec<(PathBuf, String)>, UiError> {
+        let mut roots = vec![];
+        for (name, pack) in &self.packs {
+            if let PackConfig::Local(local) = pack {
+                for path in &local.paths {
+                    roots.push((crate::expand_tilde_path(path)?, name.clone()));
+                }
+            }
+        }
+        Ok(roots)
+    }
+
+    /// Same shape as `kitowall settings get`: like the CLI, the mode comes from
+    /// state.json (what is running), the rest from config.json.
+    pub fn settings(&self, state: &RuntimeState) -> SettingsPayload {
+        SettingsPayload {
+            schema_version: Some(crate::models::CLI_SCHEMA_VERSION),
+            mode: state.mode.clone(),
+            rotation_interval_seconds: self.rotation_interval_seconds,
+            transition: self.transition.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+/// Reads the config. A missing file yields the defaults, like the CLI; a file
+/// that can't be parsed is a `BadConfig` naming the path and the position.
+pub fn load() -> Result<Config, UiError> {
+    let path = crate::paths::config_file()?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(UiError::BadConfig(format!("{}: {e}", path.display()))),
+    };
+    parse(&raw).map_err(|msg| UiError::BadConfig(format!("{}: {msg}", path.display())))
+}
+
+pub fn parse(raw: &str) -> Result<Config, String> {
+    let mut config: Config = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    if config.schema_version > CONFIG_SCHEMA_VERSION {
+        return Err(format!(
+            "schemaVersion {} is newer than this UI supports ({CONFIG_SCHEMA_VERSION}); update kitowall-ui",
+            config.schema_version
+        ));
+    }
+    config.warnings = skipped_packs(raw);
+    Ok(config)
+}
+
+/// Why each pack dropped by `valid_packs` was rejected.
+fn skipped_packs(raw: &str) -> Vec<String> {
+    let Ok(Value::Object(root)) = serde_json::from_str::<Value>(raw) else {
+        return vec![];
+    };
+    let Some(Value::Object(packs)) = root.get("packs") else {
+        return vec![];
+    };
+    packs
+        .iter()
+        .filter_map(|(name, value)| {
+            let err = serde_json::from_value::<PackConfig>(value.clone()).err()?;
+            Some(format!("pack {name:?} in config.json was skipped: {err}"))
+        })
+        .collect()
+}
+
+/// Drops packs the CLI would skip too (unknown type, wrong field types)
+/// instead of rejecting the whole file; `parse` reports them as warnings.
+fn valid_packs<'de, D: Deserializer<'de>>(de: D) -> Result<BTreeMap<String, PackConfig>, D::Error> {
+    let raw: Option<BTreeMap<String, Value>> = Option::deserialize(de)?;
+    let mut packs = BTreeMap::new();
+    for (name, value) in raw.unwrap_or_default() {
+        if let Ok(pack) = serde_json::from_value::<PackConfig>(value) {
+            packs.insert(name, pack);
+        }
+    }
+    Ok(packs)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// A single string or an array of strings, kept as-is.
+fn one_or_many<'de, D: Deserializer<'de>>(de: D) -> Result<Vec<String>, D::Error> {
+    Ok(match Option::<OneOrMany>::deserialize(de)? {
+        None => vec![],
+        Some(OneOrMany::One(s)) => vec![s],
+        Some(OneOrMany::Many(v)) => v,
+    })
+}
+
+/// Like `normalizeStringArray` in the CLI: a comma-separated string or an
+/// array, trimmed, empty items dropped.
+fn string_list<'de, D: Deserializer<'de>>(de: D) -> Result<Vec<String>, D::Error> {
+    let items = match Option::<OneOrMany>::deserialize(de)? {
+        None => vec![],
+        Some(OneOrMany::One(s)) => s.split(',').map(str::to_string).collect(),
+        Some(OneOrMany::Many(v)) => v,
+    };
+    Ok(items
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
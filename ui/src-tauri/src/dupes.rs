@@ -0,0 +1,96 @@
+// Near-duplicate finder for the wallpaper library. Every image gets a 64-bit
+// difference hash (dHash) of its thumbnail: shrunk to 9x8 grey, each bit says
+// whether a pixel is brighter than its right neighbour. The hash survives
+// rescaling and recompression, so one picture hydrated from two sources at
+// different resolutions lands a few bits away from itself.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::UiError;
+use crate::library::{Library, WallpaperItem};
+
+/// Differing bits still counted as the same picture.
+pub const DEFAULT_MAX_DISTANCE: u32 = 6;
+
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image::imageops::resize(&image.to_luma8(), 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash = hash << 1 | u64::from(small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0]);
+        }
+    }
+    hash
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// The copy to keep: the largest resolution, then the largest file.
+    pub keep: String,
+    /// Every copy, `keep` first.
+    pub items: Vec<WallpaperItem>,
+}
+
+fn pixels(item: &WallpaperItem) -> u64 {
+    u64::from(item.width.unwrap_or(0)) * u64::from(item.height.unwrap_or(0))
+}
+
+/// Groups items whose hashes are at most `max_distance` bits apart, including
+/// through a chain of near matches. Items without a twin are left out.
+pub fn group(items: Vec<(WallpaperItem, u64)>, max_distance: u32) -> Vec<DuplicateGroup> {
+    let mut parent: Vec<usize> = (0..items.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..items.len() {
+        for j in i + 1..items.len() {
+            if (items[i].1 ^ items[j].1).count_ones() <= max_distance {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    let mut sets: BTreeMap<usize, Vec<WallpaperItem>> = BTreeMap::new();
+    for (i, (item, _)) in items.into_iter().enumerate() {
+        let root = find(&mut parent, i);
+        sets.entry(root).or_default().push(item);
+    }
+    sets.into_values()
+        .filter(|set| set.len() > 1)
+        .map(|mut set| {
+            set.sort_by(|a, b| {
+                pixels(b)
+                    .cmp(&pixels(a))
+                    .then(b.size.cmp(&a.size))
+                    .then_with(|| a.path.cmp(&b.path))
+            });
+            DuplicateGroup {
+                keep: set[0].path.clone(),
+                items: set,
+            }
+        })
+        .collect()
+}
+
+/// Deletes one library image, to the desktop trash unless `permanent`. Paths
+/// outside the library are refused.
+pub fn remove(library: &Library, path: &str, permanent: bool) -> Result<(), UiError> {
+    let item = library
+        .item(Path::new(path))
+        .ok_or_else(|| UiError::invalid(format!("not an image in the wallpaper library: {path}")))?;
+    if permanent {
+        std::fs::remove_file(&item.path)?;
+    } else {
+        trash::delete(&item.path).map_err(|e| UiError::Host(format!("could not move {} to the trash: {e}", item.path)))?;
+    }
+    Ok(())
+}
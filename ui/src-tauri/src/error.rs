@@ -0,0 +1,210 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use thiserror::Error;
+
+const REDACTED: &str = "<redacted>";
+/// Query parameters that carry credentials in source adapter URLs.
+const SECRET_QUERY_KEYS: [&str; 4] = ["apikey=", "api_key=", "client_id=", "key="];
+
+#[derive(Default)]
+struct Secrets {
+    loose: HashSet<String>,
+    /// Secrets stored under a name (a pack's key, the Workshop key) so a
+    /// replacement or removal also stops masking the old value.
+    named: HashMap<String, String>,
+}
+
+fn known_secrets() -> &'static Mutex<Secrets> {
+    static SECRETS: OnceLock<Mutex<Secrets>> = OnceLock::new();
+    SECRETS.get_or_init(|| Mutex::new(Secrets::default()))
+}
+
+/// Remembers a secret handed to the CLI so it is masked in every serialized error.
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    // Very short values would mask unrelated text.
+    if secret.len() < 6 {
+        return;
+    }
+    if let Ok(mut set) = known_secrets().lock() {
+        set.loose.insert(secret.to_string());
+    }
+}
+
+/// Like [`register_secret`], but replaces whatever was registered under `name`.
+pub fn register_named_secret(name: &str, secret: &str) {
+    forget_named_secret(name);
+    let secret = secret.trim();
+    if secret.len() < 6 {
+        return;
+    }
+    if let Ok(mut set) = known_secrets().lock() {
+        set.named.insert(name.to_string(), secret.to_string());
+    }
+}
+
+/// Stops masking the secret registered under `name`, e.g. after its pack is removed.
+pub fn forget_named_secret(name: &str) {
+    if let Ok(mut set) = known_secrets().lock() {
+        if let Some(old) = set.named.remove(name) {
+            set.loose.remove(&old);
+        }
+    }
+}
+
+/// Masks registered secrets and credential query parameters in `text`.
+pub fn scrub(text: &str) -> String {
+    let mut out = text.to_string();
+    if let Ok(set) = known_secrets().lock() {
+        for secret in set.loose.iter().chain(set.named.values()) {
+            out = out.replace(secret.as_str(), REDACTED);
+        }
+    }
+    for key in SECRET_QUERY_KEYS {
+        let mut from = 0;
+        while let Some(pos) = out[from..].find(key) {
+            let start = from + pos + key.len();
+            // Only whole parameter names: `?key=` / `&key=`, not `monkey=`.
+            let boundary = start == key.len() || matches!(out.as_bytes()[from + pos - 1], b'?' | b'&');
+            let end = out[start..]
+                .find(|c: char| c == '&' || c == '"' || c == '\'' || c.is_whitespace())
+                .map_or(out.len(), |i| start + i);
+            if boundary && end > start && &out[start..end] != REDACTED {
+                out.replace_range(start..end, REDACTED);
+                from = start + REDACTED.len();
+            } else {
+                from = end.max(start);
+            }
+        }
+    }
+    out
+}
+
+const BOOTSTRAP_HINT: &str =
+    "Run scripts/bootstrap-host.sh or install globally (`npm i -g kitowall`), then retry.";
+
+/// Error returned by every Tauri command. Serialized as
+/// `{code, message, exitCode, stderr, action, cliCode}` so the UI can branch on
+/// `code`; `cliCode` carries the CLI's own code for `action_required`.
+#[derive(Debug, Error)]
+pub enum UiError {
+    #[error("{0} not found on host")]
+    CliMissing(String),
+    #[error("command failed: {message}")]
+    CommandFailed {
+        message: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    #[error("action required: {message}")]
+    ActionRequired {
+        message: String,
+        code: Option<String>,
+        hint: Option<String>,
+    },
+    #[error("invalid json: {0}")]
+    InvalidJson(String),
+    #[error("command timed out: {0}")]
+    Timeout(String),
+    #[error("command cancelled: {0}")]
+    Cancelled(String),
+    #[error("CLI/UI schema mismatch: {0}")]
+    SchemaMismatch(String),
+    #[error("incompatible kitowall CLI: {0}")]
+    CliIncompatible(String),
+    #[error("invalid config.json: {0}")]
+    BadConfig(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    Host(String),
+}
+
+impl UiError {
+    pub fn failed(message: impl Into<String>) -> Self {
+        UiError::CommandFailed {
+            message: message.into(),
+            exit_code: None,
+            stderr: String::new(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        UiError::InvalidArgument(message.into())
+    }
+
+    /// Stable identifier for the frontend; never rename an existing code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            UiError::CliMissing(_) => "cli_missing",
+            UiError::CommandFailed { .. } => "command_failed",
+            UiError::ActionRequired { .. } => "action_required",
+            UiError::InvalidJson(_) => "invalid_json",
+            UiError::Timeout(_) => "timeout",
+            UiError::Cancelled(_) => "cancelled",
+            UiError::SchemaMismatch(_) => "schema_mismatch",
+            UiError::CliIncompatible(_) => "cli_incompatible",
+            UiError::BadConfig(_) => "bad_config",
+            UiError::InvalidArgument(_) => "invalid_argument",
+            UiError::Host(_) => "host_error",
+        }
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            UiError::CommandFailed { exit_code, .. } => *exit_code,
+            UiError::ActionRequired { .. } => Some(2),
+            _ => None,
+        }
+    }
+
+    pub fn stderr(&self) -> Option<&str> {
+        match self {
+            UiError::CommandFailed { stderr, .. } if !stderr.trim().is_empty() => Some(stderr.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn cli_code(&self) -> Option<&str> {
+        match self {
+            UiError::ActionRequired { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn action(&self) -> Option<String> {
+        let text = match self {
+            UiError::CliMissing(_) => BOOTSTRAP_HINT,
+            UiError::ActionRequired { hint, .. } => return hint.clone(),
+            UiError::Timeout(_) => "Retry, or raise KITOWALL_UI_TIMEOUT_SEC for slow hosts.",
+            UiError::SchemaMismatch(_) | UiError::CliIncompatible(_) => {
+                "Update kitowall and kitowall-ui to matching versions."
+            }
+            UiError::BadConfig(_) => "Fix the config.json named above or re-run `kitowall init`.",
+            UiError::InvalidJson(_) => "Check that KITOWALL_CMD points to the kitowall CLI.",
+            _ => return None,
+        };
+        Some(text.to_string())
+    }
+}
+
+impl Serialize for UiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("UiError", 6)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &scrub(&self.to_string()))?;
+        s.serialize_field("exitCode", &self.exit_code())?;
+        s.serialize_field("stderr", &self.stderr().map(scrub))?;
+        s.serialize_field("action", &self.action())?;
+        s.serialize_field("cliCode", &self.cli_code())?;
+        s.end()
+    }
+}
+
+impl From<std::io::Error> for UiError {
+    fn from(err: std::io::Error) -> Self {
+        UiError::Host(err.to_string())
+    }
+}
@@ -0,0 +1,121 @@
+// Host process spawning. Inside a Flatpak (or a toolbox-style container) the
+// tools we drive live on the host, so commands are re-targeted through
+// `flatpak-spawn --host` and their environment is passed as `--env=` flags.
+
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Session variables the host side needs to find the compositor and the user bus.
+const FORWARDED_ENV: [&str; 7] = [
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+    "XDG_RUNTIME_DIR",
+    "XDG_CURRENT_DESKTOP",
+    "HYPRLAND_INSTANCE_SIGNATURE",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "LANG",
+];
+/// Prefixes forwarded as a whole (CLI overrides and remote source keys).
+const FORWARDED_PREFIXES: [&str; 4] = ["KITOWALL_", "KITSUNE_", "WALLHAVEN_", "UNSPLASH_"];
+/// Base directories Flatpak points into the app's sandbox; the host values
+/// are exported as `HOST_<name>` when the user set them.
+const XDG_BASE_DIRS: [&str; 4] = ["XDG_CONFIG_HOME", "XDG_STATE_HOME", "XDG_CACHE_HOME", "XDG_DATA_HOME"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum HostMode {
+    Native,
+    Sandboxed { reason: String },
+}
+
+impl HostMode {
+    fn detect() -> Self {
+        if Path::new("/.flatpak-info").exists() {
+            return HostMode::Sandboxed { reason: "/.flatpak-info".to_string() };
+        }
+        match std::env::var("container") {
+            Ok(kind) if !kind.trim().is_empty() => HostMode::Sandboxed { reason: format!("container={kind}") },
+            _ => HostMode::Native,
+        }
+    }
+
+    pub fn is_sandboxed(&self) -> bool {
+        matches!(self, HostMode::Sandboxed { .. })
+    }
+}
+
+pub fn mode() -> &'static HostMode {
+    static MODE: OnceLock<HostMode> = OnceLock::new();
+    MODE.get_or_init(HostMode::detect)
+}
+
+/// Value of an XDG base directory variable as the host session sees it.
+pub fn xdg_var(name: &str) -> Option<String> {
+    let key = if mode().is_sandboxed() { format!("HOST_{name}") } else { name.to_string() };
+    std::env::var(key).ok()
+}
+
+/// Builds a command that runs `program` on the host with `env` set.
+pub fn command(program: &str, env: &[(&str, String)]) -> Command {
+    if !mode().is_sandboxed() {
+        let mut cmd = Command::new(program);
+        cmd.envs(env.iter().map(|(k, v)| (k, v)));
+        return cmd;
+    }
+
+    flatpak_spawn(program, env, std::env::vars(), xdg_var)
+}
+
+/// The `flatpak-spawn --host` command for `program`: forwards the session and
+/// kitowall variables found in `vars` and the host's XDG base dirs from
+/// `xdg`, with `env` taking precedence over both.
+pub fn flatpak_spawn(
+    program: &str,
+    env: &[(&str, String)],
+    vars: impl IntoIterator<Item = (String, String)>,
+    xdg: impl Fn(&str) -> Option<String>,
+) -> Command {
+    let mut cmd = Command::new("flatpak-spawn");
+    // --watch-bus ties the host process to us, so kill-on-timeout and cancel still work.
+    cmd.args(["--host", "--watch-bus"]);
+    for (key, value) in vars {
+        let forward = FORWARDED_ENV.contains(&key.as_str())
+            || FORWARDED_PREFIXES.iter().any(|p| key.starts_with(p));
+        if forward && !env.iter().any(|(k, _)| *k == key) {
+            cmd.arg(format!("--env={key}={value}"));
+        }
+    }
+    for name in XDG_BASE_DIRS {
+        if let Some(value) = xdg(name).filter(|_| !env.iter().any(|(k, _)| *k == name)) {
+            cmd.arg(format!("--env={name}={value}"));
+        }
+    }
+    for (key, value) in env {
+        cmd.arg(format!("--env={key}={value}"));
+    }
+    cmd.arg(program);
+    cmd
+}
+
+/// Whether `path` exists on the host. The sandbox has its own filesystem view,
+/// so there the check is a `test -e` run on the host.
+pub fn path_exists(path: &Path) -> bool {
+    if !mode().is_sandboxed() {
+        return path.exists();
+    }
+    command("test", &[])
+        .arg("-e")
+        .arg(path)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// `flatpak-spawn` reports a missing host binary as a normal failed run; this
+/// recognizes that case so it can surface like a native spawn `NotFound`.
+pub fn is_host_not_found(exit_code: Option<i32>, stderr: &str) -> bool {
+    mode().is_sandboxed()
+        && (exit_code == Some(127)
+            || (stderr.contains("Failed to start command") && stderr.contains("No such file or directory")))
+}
@@ -0,0 +1,455 @@
+// On-disk index of the wallpaper library in the UI cache dir. Directories are
+// keyed by canonical path with their mtime. A full refresh, on first use or
+// when the library changes, re-reads only the ones whose mtime moved (a file
+// added, removed or renamed in them) and stats the known files of the rest, so
+// edits made while the UI was closed are picked up. Once the library watcher
+// runs, its events mark the directories they touch and a listing re-reads just
+// those; everything else is served from memory. What gets indexed follows the
+// library's scan rules; a change to them re-reads every directory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::error::UiError;
+use crate::library::{self, ImageHeader, Library, LibraryRoot, ScanIssue, WallpaperItem};
+
+/// Bump when the record layout changes; older files are discarded.
+const INDEX_VERSION: u32 = 4;
+const INDEX_FILE: &str = "library.json";
+/// A directory modified this close to when it was read may have changed again
+/// within the same mtime tick, so it is re-read until it settles.
+const RACY_NS: u64 = 2_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRecord {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified_ms: u64,
+    /// `None` for videos and when the header could not be read.
+    pub header: Option<ImageHeader>,
+}
+
+impl FileRecord {
+    /// Stats `path`'s metadata into a record and reads the image header, or
+    /// keeps `previous`'s when the file is unchanged.
+    pub fn read(path: PathBuf, meta: &Metadata, previous: Option<&FileRecord>) -> Self {
+        let size = meta.len();
+        let modified_ms = meta.modified().map_or(0, |t| library::since_epoch(t).as_millis() as u64);
+        let header = match previous {
+            Some(old) if old.path == path && old.size == size && old.modified_ms == modified_ms => old.header.clone(),
+            _ if library::is_video_ext(&path) => None,
+            _ => library::read_header(&path),
+        };
+        Self {
+            path,
+            size,
+            modified_ms,
+            header,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DirRecord {
+    mtime_ns: u64,
+    read_ns: u64,
+    subdirs: Vec<PathBuf>,
+    files: Vec<FileRecord>,
+    /// Entries that could not be read; a directory with any is re-read on
+    /// every refresh until they clear up.
+    unreadable: Vec<ScanIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    version: u32,
+    /// The scan rules the directories were read under.
+    rules: String,
+    dirs: BTreeMap<PathBuf, DirRecord>,
+    /// Directories that could not even be stat'ed in the last refresh.
+    #[serde(skip)]
+    missing: Vec<ScanIssue>,
+}
+
+impl Default for LibraryIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            rules: String::new(),
+            dirs: BTreeMap::new(),
+            missing: vec![],
+        }
+    }
+}
+
+pub fn index_dir() -> Result<PathBuf, UiError> {
+    Ok(crate::paths::cache_dir()?.join("ui-index"))
+}
+
+fn mtime_ns(meta: &Metadata) -> u64 {
+    meta.modified().map_or(0, |t| library::since_epoch(t).as_nanos() as u64)
+}
+
+impl LibraryIndex {
+    /// The saved index, or an empty one if it is missing, unreadable or from
+    /// another version; it is only a cache.
+    pub fn load() -> Self {
+        let Ok(path) = index_dir().map(|d| d.join(INDEX_FILE)) else {
+            return Self::default();
+        };
+        fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<Self>(&raw).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), UiError> {
+        let dir = index_dir()?;
+        fs::create_dir_all(&dir)?;
+        let tmp = dir.join(format!("{INDEX_FILE}.tmp"));
+        fs::write(&tmp, serde_json::to_vec(self).map_err(|e| UiError::Host(e.to_string()))?)?;
+        fs::rename(&tmp, dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+
+    /// Brings the index in line with the disk under `library`'s roots and
+    /// drops everything else. Returns whether anything changed.
+    pub fn refresh(&mut self, library: &Library) -> bool {
+        let mut previous = std::mem::take(&mut self.dirs);
+        let rules = library.scan_rules();
+        let rules_changed = self.rules != rules;
+        self.rules = rules;
+        self.missing.clear();
+        let mut changed = rules_changed;
+        // By `library::dir_id`, so a directory reached twice (symlink or bind
+        // mount loops, nested roots) is only read once.
+        let mut visited = HashSet::new();
+        for root in &library.roots {
+            let mut stack = vec![root.path.clone()];
+            while let Some(dir) = stack.pop() {
+                let meta = match fs::metadata(&dir) {
+                    Ok(meta) => meta,
+                    Err(err) => {
+                        self.missing.push(ScanIssue {
+                            path: dir,
+                            error: err.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                if !visited.insert(library::dir_id(&dir, &meta)) {
+                    continue;
+                }
+                let mtime = mtime_ns(&meta);
+                let record = match previous.remove(&dir) {
+                    Some(mut old)
+                        if !rules_changed
+                            && old.unreadable.is_empty()
+                            && old.mtime_ns == mtime
+                            && old.read_ns > mtime.saturating_add(RACY_NS) =>
+                    {
+                        changed |= restat_files(&mut old);
+                        old
+                    }
+                    old => {
+                        let fresh = read_dir_record(library, &dir, root, mtime, old.as_ref());
+                        changed |= old.is_none_or(|old| {
+                            old.subdirs != fresh.subdirs || old.files != fresh.files || old.unreadable != fresh.unreadable
+                        });
+                        fresh
+                    }
+                };
+                stack.extend(record.subdirs.iter().cloned());
+                self.dirs.insert(dir, record);
+            }
+        }
+        changed || !previous.is_empty()
+    }
+
+    /// Re-reads just `dirs`, the directories the watcher saw change, plus any
+    /// new subdirectories they list; ones that are gone are dropped with
+    /// everything below them. Returns whether anything changed.
+    pub fn refresh_dirs(&mut self, library: &Library, dirs: BTreeSet<PathBuf>) -> bool {
+        let mut changed = false;
+        let mut visited = HashSet::new();
+        let mut stack: Vec<PathBuf> = dirs.into_iter().collect();
+        while let Some(dir) = stack.pop() {
+            let Some(root) = library.root_for(&dir) else {
+                continue;
+            };
+            // Only directories the scan reaches: indexed ones, roots, and new
+            // subdirectories an indexed parent lists under the scan rules.
+            let listed = dir.parent().and_then(|p| self.dirs.get(p)).is_some_and(|p| p.subdirs.contains(&dir));
+            if !(listed || dir == root.path || self.dirs.contains_key(&dir)) {
+                continue;
+            }
+            let meta = match fs::metadata(&dir) {
+                Ok(meta) if meta.is_dir() => meta,
+                _ => {
+                    changed |= self.forget_tree(&dir);
+                    continue;
+                }
+            };
+            if !visited.insert(library::dir_id(&dir, &meta)) {
+                continue;
+            }
+            let old = self.dirs.remove(&dir);
+            let fresh = read_dir_record(library, &dir, root, mtime_ns(&meta), old.as_ref());
+            if let Some(old) = &old {
+                for gone in old.subdirs.iter().filter(|d| !fresh.subdirs.contains(d)) {
+                    changed |= self.forget_tree(gone);
+                }
+            }
+            stack.extend(fresh.subdirs.iter().filter(|d| !self.dirs.contains_key(*d)).cloned());
+            changed |= old.is_none_or(|old| {
+                old.subdirs != fresh.subdirs || old.files != fresh.files || old.unreadable != fresh.unreadable
+            });
+            self.dirs.insert(dir, fresh);
+        }
+        changed
+    }
+
+    /// Drops `dir` and every directory below it.
+    fn forget_tree(&mut self, dir: &Path) -> bool {
+        let before = self.dirs.len();
+        self.dirs.retain(|path, _| !path.starts_with(dir));
+        self.dirs.len() != before
+    }
+
+    /// Paths the last refresh could not read, by path.
+    pub fn unreadable(&self) -> Vec<ScanIssue> {
+        let mut issues: Vec<ScanIssue> = self
+            .missing
+            .iter()
+            .chain(self.dirs.values().flat_map(|d| d.unreadable.iter()))
+            .cloned()
+            .collect();
+        issues.sort_by(|a, b| a.path.cmp(&b.path));
+        issues
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &FileRecord> {
+        self.dirs.values().flat_map(|d| d.files.iter())
+    }
+
+    /// Every indexed image and video, newest first.
+    pub fn items(&self, library: &Library) -> Vec<WallpaperItem> {
+        let mut items: Vec<WallpaperItem> = self
+            .files()
+            .filter_map(|f| Some(library::make_item(library.root_for(&f.path)?, f)))
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.modified_ms));
+        items
+    }
+}
+
+/// Re-stats the files of a directory whose listing has not changed. Files
+/// rewritten in place get a fresh record (and header); ones that can no longer
+/// be stat'ed, like a symlink whose target went away, are dropped. Returns
+/// whether any record changed.
+fn restat_files(record: &mut DirRecord) -> bool {
+    let before = record.files.len();
+    let mut changed = false;
+    record.files = std::mem::take(&mut record.files)
+        .into_iter()
+        .filter_map(|old| {
+            let meta = fs::metadata(&old.path).ok().filter(Metadata::is_file)?;
+            let fresh = FileRecord::read(old.path.clone(), &meta, Some(&old));
+            changed |= fresh != old;
+            Some(fresh)
+        })
+        .collect();
+    changed || record.files.len() != before
+}
+
+/// Lists one directory. Paths stay canonical: `dir` is, and symlinks are
+/// resolved (and dropped when they point outside the root, as before). Entries
+/// the library's rules leave out are skipped, and ones that can't be read are
+/// noted. Image headers are only read for files that are new or changed since
+/// `previous`.
+fn read_dir_record(
+    library: &Library,
+    dir: &Path,
+    root: &LibraryRoot,
+    mtime_ns: u64,
+    previous: Option<&DirRecord>,
+) -> DirRecord {
+    let mut record = DirRecord {
+        mtime_ns,
+        read_ns: library::since_epoch(SystemTime::now()).as_nanos() as u64,
+        subdirs: vec![],
+        files: vec![],
+        unreadable: vec![],
+    };
+    let issue = |path: &Path, err: std::io::Error| ScanIssue {
+        path: path.to_path_buf(),
+        error: err.to_string(),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            record.unreadable.push(issue(dir, err));
+            return record;
+        }
+    };
+    let previous: HashMap<&Path, &FileRecord> = previous
+        .map(|p| p.files.iter().map(|f| (f.path.as_path(), f)).collect())
+        .unwrap_or_default();
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                record.unreadable.push(issue(dir, err));
+                continue;
+            }
+        };
+        let mut path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(err) => {
+                record.unreadable.push(issue(&path, err));
+                continue;
+            }
+        };
+        if file_type.is_symlink() {
+            match fs::canonicalize(&path) {
+                Ok(target) if target.starts_with(&root.path) => path = target,
+                _ => continue,
+            }
+        }
+        let meta = match fs::metadata(&path) {
+            Ok(meta) => meta,
+            Err(err) => {
+                record.unreadable.push(issue(&path, err));
+                continue;
+            }
+        };
+        let Ok(rel) = path.strip_prefix(&root.path) else {
+            continue;
+        };
+        if !library.admits(root, rel, meta.is_dir()) {
+            continue;
+        }
+        if meta.is_dir() {
+            record.subdirs.push(path);
+        } else if meta.is_file() && library::is_media_ext(&path) {
+            let old = previous.get(path.as_path()).copied();
+            record.files.push(FileRecord::read(path, &meta, old));
+        }
+    }
+    record
+}
+
+/// The index shared by the listing command and the library watcher, loaded
+/// from disk on first use. While the watcher for a library is live, listings
+/// come from memory and only re-read the directories it marked dirty.
+#[derive(Default)]
+pub struct SharedIndex {
+    shared: Mutex<Shared>,
+}
+
+#[derive(Default)]
+struct Shared {
+    index: Option<LibraryIndex>,
+    /// The library a running watcher keeps this index current for.
+    live: Option<Library>,
+    dirty: BTreeSet<PathBuf>,
+    /// `index.items()` as of the last change, while live.
+    listing: Option<Vec<WallpaperItem>>,
+    /// Keep the index in memory only, for tests.
+    ephemeral: bool,
+}
+
+impl SharedIndex {
+    /// Starts empty and never touches the cache dir.
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        let shared = Shared {
+            index: Some(LibraryIndex::default()),
+            ephemeral: true,
+            ..Shared::default()
+        };
+        Self {
+            shared: Mutex::new(shared),
+        }
+    }
+
+    /// The listing for `library`. While live it re-reads only dirty
+    /// directories; otherwise it refreshes against the disk first. Saves the
+    /// index when it changed.
+    pub fn list(&self, library: &Library) -> Result<Vec<WallpaperItem>, UiError> {
+        let mut guard = self
+            .shared
+            .lock()
+            .map_err(|_| UiError::Host("library index lock poisoned".to_string()))?;
+        let shared = &mut *guard;
+        let index = shared.index.get_or_insert_with(LibraryIndex::load);
+        let live = shared.live.as_ref() == Some(library) && shared.listing.is_some();
+        let changed = if live {
+            let dirty = std::mem::take(&mut shared.dirty);
+            !dirty.is_empty() && index.refresh_dirs(library, dirty)
+        } else {
+            shared.dirty.clear();
+            index.refresh(library)
+        };
+        if changed && !shared.ephemeral {
+            if let Err(err) = index.save() {
+                eprintln!("[kitowall-ui] could not save the library index: {err}");
+            }
+        }
+        if changed || !live {
+            shared.listing = Some(index.items(library));
+        }
+        Ok(shared.listing.clone().unwrap_or_default())
+    }
+
+    /// From now on `library`'s watcher reports changes through [`mark_dirty`].
+    ///
+    /// [`mark_dirty`]: SharedIndex::mark_dirty
+    pub fn go_live(&self, library: &Library) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.live = Some(library.clone());
+            shared.dirty.clear();
+        }
+    }
+
+    /// The watcher stopped; listings refresh against the disk again.
+    pub fn stop_live(&self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.live = None;
+            shared.listing = None;
+        }
+    }
+
+    /// Paths from one burst of watcher events: the directories holding them,
+    /// and the paths themselves when they are directories, get re-read by the
+    /// next listing.
+    pub fn mark_dirty(&self, touched: &[PathBuf]) {
+        let dirs: Vec<PathBuf> = touched
+            .iter()
+            .flat_map(|path| [path.is_dir().then(|| path.clone()), path.parent().map(Path::to_path_buf)])
+            .flatten()
+            .collect();
+        if let Ok(mut shared) = self.shared.lock() {
+            if shared.live.is_some() {
+                shared.dirty.extend(dirs);
+            }
+        }
+    }
+
+    /// What the last listing could not read.
+    pub fn unreadable(&self) -> Vec<ScanIssue> {
+        self.shared
+            .lock()
+            .ok()
+            .and_then(|shared| shared.index.as_ref().map(LibraryIndex::unreadable))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,572 @@
+// The wallpaper library: every image and video clip under the download root
+// (pack = its first folder), under each local pack's paths (pack = that pack's
+// name) and under the live library (pack = `live`), plus the bookkeeping that
+// turns filesystem events into added/removed/modified items.
+// The `[library]` rules in ui.toml (depth, hidden files, excludes) decide which
+// paths under a root belong to it.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::error::UiError;
+use crate::index::FileRecord;
+use crate::ui_config::LibrarySettings;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WallpaperItem {
+    pub path: String,
+    pub pack: String,
+    pub file_name: String,
+    pub kind: MediaKind,
+    pub modified_ms: u64,
+    /// Bytes on disk.
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// `width / height`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect: Option<f64>,
+    /// Detected from the file's magic bytes, not its extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// `#rrggbb`, known once the thumbnail has been made.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_color: Option<String>,
+    /// Cached thumbnail, once one has been made; a poster frame for videos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumb: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Image,
+    /// GIF, APNG or animated WebP; swww plays these.
+    Animated,
+    /// A clip for the live backend.
+    Video,
+}
+
+/// What an image's header says, read without decoding any pixels.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageHeader {
+    pub width: u32,
+    pub height: u32,
+    pub format: String,
+    pub animated: bool,
+}
+
+pub fn read_header(path: &Path) -> Option<ImageHeader> {
+    use imagesize::{Compression, ImageType};
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let kind = imagesize::reader_type(&mut reader).ok()?;
+    let size = kind.reader_size(&mut reader).ok()?;
+    let format = match kind {
+        ImageType::Jpeg => "jpeg",
+        ImageType::Png => "png",
+        ImageType::Webp => "webp",
+        ImageType::Bmp => "bmp",
+        ImageType::Gif => "gif",
+        ImageType::Heif(Compression::Av1) => "avif",
+        ImageType::Heif(_) => "heif",
+        _ => return None,
+    };
+    reader.rewind().ok()?;
+    Some(ImageHeader {
+        width: u32::try_from(size.width).ok()?,
+        height: u32::try_from(size.height).ok()?,
+        format: format.to_string(),
+        animated: is_animated(&mut reader, format),
+    })
+}
+
+/// Bytes searched for a GIF's looping extension, which follows the global
+/// color table.
+const GIF_SNIFF: u64 = 4096;
+
+/// Whether the file holds more than a still frame, from its container alone:
+/// a looping GIF, a PNG with an `acTL` chunk, a WebP with the animation flag.
+fn is_animated(reader: &mut (impl Read + Seek), format: &str) -> bool {
+    match format {
+        "gif" => {
+            let mut head = vec![];
+            let _ = reader.take(GIF_SNIFF).read_to_end(&mut head);
+            head.windows(11).any(|w| w == b"NETSCAPE2.0" || w == b"ANIMEXTS1.0")
+        }
+        "png" => {
+            // Chunks after the 8-byte signature; acTL must come before IDAT.
+            let mut offset = 8;
+            let mut chunk = [0u8; 8];
+            while reader.seek(SeekFrom::Start(offset)).is_ok() && reader.read_exact(&mut chunk).is_ok() {
+                match &chunk[4..] {
+                    b"acTL" => return true,
+                    b"IDAT" | b"IEND" => return false,
+                    _ => {}
+                }
+                let len = u64::from(u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+                offset += 12 + len;
+            }
+            false
+        }
+        "webp" => {
+            // RIFF header, then a VP8X chunk whose flags byte has bit 1 set.
+            let mut head = [0u8; 21];
+            reader.read_exact(&mut head).is_ok() && &head[12..16] == b"VP8X" && head[20] & 0x02 != 0
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryRoot {
+    /// Canonical path.
+    pub path: PathBuf,
+    /// Local pack owning this root, `live` for the live library; `None` for
+    /// the download root.
+    pub pack: Option<String>,
+    pub exclude: Excludes,
+}
+
+/// A root's exclude globs, matched against paths relative to it.
+#[derive(Debug, Clone, Default)]
+pub struct Excludes {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl PartialEq for Excludes {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns
+    }
+}
+
+impl Eq for Excludes {}
+
+impl Excludes {
+    pub fn new(patterns: Vec<String>) -> Result<Self, String> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &patterns {
+            builder.add(Glob::new(pattern).map_err(|e| e.to_string())?);
+        }
+        let set = builder.build().map_err(|e| e.to_string())?;
+        Ok(Self { patterns, set })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    /// As configured, for display.
+    pub download_root: PathBuf,
+    /// Roots that exist on disk, download root first.
+    pub roots: Vec<LibraryRoot>,
+    /// Directory levels scanned below each root.
+    pub max_depth: usize,
+    pub include_hidden: bool,
+}
+
+/// A path the scan could not read, reported instead of silently skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanIssue {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Identity of a directory however it was reached: `(device, inode)` on unix.
+#[cfg(unix)]
+pub type DirId = (u64, u64);
+/// Elsewhere the canonical path, which also resolves symlinks and junctions.
+#[cfg(not(unix))]
+pub type DirId = PathBuf;
+
+#[cfg(unix)]
+pub fn dir_id(_path: &Path, meta: &fs::Metadata) -> DirId {
+    (meta.dev(), meta.ino())
+}
+
+#[cfg(not(unix))]
+pub fn dir_id(path: &Path, _meta: &fs::Metadata) -> DirId {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Pack name of the live library's root.
+pub const LIVE_PACK: &str = "live";
+/// Where the CLI keeps the live index, thumbnails and temp files, inside the
+/// live root; none of it is a wallpaper.
+const LIVE_INTERNAL: &str = ".kitowall/**";
+
+fn ext_of(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+pub fn is_image_ext(path: &Path) -> bool {
+    matches!(ext_of(path).as_str(), "jpg" | "jpeg" | "png" | "webp" | "bmp" | "gif" | "avif")
+}
+
+pub fn is_video_ext(path: &Path) -> bool {
+    matches!(ext_of(path).as_str(), "mp4" | "m4v" | "webm" | "mkv" | "mov")
+}
+
+/// Files the library lists: images and video clips.
+pub fn is_media_ext(path: &Path) -> bool {
+    is_image_ext(path) || is_video_ext(path)
+}
+
+impl Library {
+    pub fn from_config(config: &Config, settings: &LibrarySettings) -> Result<Self, UiError> {
+        let download_root = config.download_root()?;
+        let mut configured = vec![(download_root.clone(), None)];
+        configured.extend(config.local_pack_roots()?.into_iter().map(|(path, pack)| (path, Some(pack))));
+        configured.push((crate::paths::live_root()?, Some(LIVE_PACK.to_string())));
+        let mut roots = vec![];
+        for (path, pack) in configured {
+            if !path.exists() {
+                continue;
+            }
+            let path = fs::canonicalize(&path)?;
+            let mut patterns = vec![];
+            if pack.as_deref() == Some(LIVE_PACK) {
+                patterns.push(LIVE_INTERNAL.to_string());
+            }
+            for (key, globs) in &settings.exclude {
+                let by_path = crate::expand_tilde_path(key).ok().and_then(|k| fs::canonicalize(k).ok());
+                if by_path.as_ref() == Some(&path) || pack.as_deref() == Some(key.as_str()) {
+                    patterns.extend(globs.iter().cloned());
+                }
+            }
+            let exclude = Excludes::new(patterns)
+                .map_err(|e| UiError::BadConfig(format!("ui.toml [library.exclude] for {}: {e}", path.display())))?;
+            roots.push(LibraryRoot { path, pack, exclude });
+        }
+        Ok(Self {
+            download_root,
+            roots,
+            max_depth: settings.max_depth,
+            include_hidden: settings.include_hidden,
+        })
+    }
+
+    pub fn load() -> Result<Self, UiError> {
+        Self::from_config(&crate::config::load()?, &crate::ui_config::load()?.library)
+    }
+
+    /// The scan rules as one string, for caches to tell when they changed.
+    pub fn scan_rules(&self) -> String {
+        let excludes: Vec<_> = self.roots.iter().map(|r| (&r.path, r.exclude.patterns())).collect();
+        serde_json::json!([self.max_depth, self.include_hidden, excludes]).to_string()
+    }
+
+    /// Whether the rules let `rel`, a path relative to `root`, into the
+    /// library: not below `max_depth`, not hidden, not excluded.
+    pub fn admits(&self, root: &LibraryRoot, rel: &Path, is_dir: bool) -> bool {
+        let names: Vec<_> = rel
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let dirs = if is_dir { names.len() } else { names.len().saturating_sub(1) };
+        let hidden = !self.include_hidden && names.iter().any(|name| name.as_encoded_bytes().starts_with(b"."));
+        dirs <= self.max_depth && !hidden && !root.exclude.set.is_match(rel)
+    }
+
+    pub fn root_paths(&self) -> Vec<PathBuf> {
+        self.roots.iter().map(|r| r.path.clone()).collect()
+    }
+
+    /// The innermost root holding `abs` (a canonical path).
+    pub fn root_for(&self, abs: &Path) -> Option<&LibraryRoot> {
+        self.roots
+            .iter()
+            .filter(|r| abs.starts_with(&r.path))
+            .max_by_key(|r| r.path.components().count())
+    }
+
+    /// The item for an image or video at `path`, attributed to the innermost
+    /// root holding it.
+    pub fn item(&self, path: &Path) -> Option<WallpaperItem> {
+        if !is_media_ext(path) {
+            return None;
+        }
+        let abs = fs::canonicalize(path).ok()?;
+        let root = self.root_for(&abs)?;
+        if !self.admits(root, abs.strip_prefix(&root.path).ok()?, false) {
+            return None;
+        }
+        let meta = fs::metadata(&abs).ok()?;
+        Some(make_item(root, &FileRecord::read(abs, &meta, None)))
+    }
+}
+
+pub fn make_item(root: &LibraryRoot, file: &FileRecord) -> WallpaperItem {
+    let abs = &file.path;
+    let header = file.header.as_ref();
+    let pack = match &root.pack {
+        Some(pack) => pack.clone(),
+        None => abs
+            .strip_prefix(&root.path)
+            .ok()
+            .and_then(|rel| rel.iter().next())
+            .map(|first| first.to_string_lossy().to_string())
+            .unwrap_or_else(|| "root".to_string()),
+    };
+    WallpaperItem {
+        path: abs.to_string_lossy().to_string(),
+        pack,
+        file_name: abs.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string(),
+        kind: match header {
+            _ if is_video_ext(abs) => MediaKind::Video,
+            Some(h) if h.animated => MediaKind::Animated,
+            _ => MediaKind::Image,
+        },
+        modified_ms: file.modified_ms,
+        size: file.size,
+        width: header.map(|h| h.width),
+        height: header.map(|h| h.height),
+        aspect: header.filter(|h| h.height > 0).map(|h| f64::from(h.width) / f64::from(h.height)),
+        format: header.map(|h| h.format.clone()),
+        dominant_color: None,
+        thumb: None,
+    }
+}
+
+pub fn modified_ms(path: &Path) -> u64 {
+    fs::metadata(path).ok().and_then(|m| m.modified().ok()).map_or(0, |t| since_epoch(t).as_millis() as u64)
+}
+
+pub fn since_epoch(time: SystemTime) -> Duration {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Images and videos below `dir` that the library's rules admit. Each directory is
+/// entered once, so symlink loops end; unreadable ones are skipped, the next
+/// index refresh reports them.
+fn walk_media(library: &Library, dir: &Path) -> Vec<PathBuf> {
+    let mut out = vec![];
+    let mut visited = HashSet::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(abs) = fs::canonicalize(&dir) else {
+            continue;
+        };
+        let Some(root) = library.root_for(&abs) else {
+            continue;
+        };
+        let admitted = abs
+            .strip_prefix(&root.path)
+            .is_ok_and(|rel| library.admits(root, rel, true));
+        let first_visit = fs::metadata(&abs).is_ok_and(|meta| visited.insert(dir_id(&abs, &meta)));
+        if !admitted || !first_visit {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&abs) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if is_media_ext(&path) {
+                out.push(path);
+            }
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Mtime,
+    Name,
+    Size,
+    Pack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+/// Filters, sort and page for `kitowall_wallpapers_list`. Every field is
+/// optional; the default is the whole library, newest first.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryQuery {
+    pub pack: Option<String>,
+    pub kind: Option<MediaKind>,
+    /// Case-insensitive substring of the file name.
+    pub q: Option<String>,
+    /// Modified at or after, in ms since the epoch.
+    pub modified_after: Option<u64>,
+    /// Modified before, in ms since the epoch.
+    pub modified_before: Option<u64>,
+    /// Images whose size is unknown never pass a minimum.
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub orientation: Option<Orientation>,
+    pub favorites_only: bool,
+    pub sort: SortKey,
+    /// Defaults to newest or largest first for `mtime` and `size`, A to Z for
+    /// `name` and `pack`.
+    pub order: Option<Order>,
+    pub offset: usize,
+    /// `None` for everything from `offset` on.
+    pub limit: Option<usize>,
+}
+
+/// Within this much of 1:1 an image counts as square.
+const SQUARE_TOLERANCE: f64 = 0.01;
+
+impl LibraryQuery {
+    fn matches(&self, item: &WallpaperItem, favorites: &HashSet<String>, q: &str) -> bool {
+        let pack_ok = self.pack.as_deref().is_none_or(|pack| item.pack == pack);
+        let kind_ok = self.kind.is_none_or(|kind| item.kind == kind);
+        let name_ok = q.is_empty() || item.file_name.to_lowercase().contains(q);
+        let after_ok = self.modified_after.is_none_or(|t| item.modified_ms >= t);
+        let before_ok = self.modified_before.is_none_or(|t| item.modified_ms < t);
+        let width_ok = self.min_width.is_none_or(|w| item.width.is_some_and(|iw| iw >= w));
+        let height_ok = self.min_height.is_none_or(|h| item.height.is_some_and(|ih| ih >= h));
+        let orientation_ok = self.orientation.is_none_or(|o| {
+            item.aspect.is_some_and(|aspect| match o {
+                Orientation::Square => (aspect - 1.0).abs() <= SQUARE_TOLERANCE,
+                Orientation::Landscape => aspect > 1.0 + SQUARE_TOLERANCE,
+                Orientation::Portrait => aspect < 1.0 - SQUARE_TOLERANCE,
+            })
+        });
+        let favorite_ok = !self.favorites_only || favorites.contains(&item.path);
+        pack_ok && kind_ok && name_ok && after_ok && before_ok && width_ok && height_ok && orientation_ok && favorite_ok
+    }
+
+    /// Filters and sorts `items`, returning how many matched and the requested
+    /// page. Ties are broken by path so pages never overlap.
+    pub fn apply(&self, items: Vec<WallpaperItem>, favorites: &HashSet<String>) -> (usize, Vec<WallpaperItem>) {
+        let q = self.q.as_deref().unwrap_or("").trim().to_lowercase();
+        let mut items: Vec<WallpaperItem> = items.into_iter().filter(|item| self.matches(item, favorites, &q)).collect();
+        let descending = match self.order {
+            Some(order) => order == Order::Desc,
+            None => matches!(self.sort, SortKey::Mtime | SortKey::Size),
+        };
+        items.sort_by(|a, b| {
+            let by_key = match self.sort {
+                SortKey::Mtime => a.modified_ms.cmp(&b.modified_ms),
+                SortKey::Name => a.file_name.to_lowercase().cmp(&b.file_name.to_lowercase()),
+                SortKey::Size => a.size.cmp(&b.size),
+                SortKey::Pack => a.pack.cmp(&b.pack),
+            };
+            let by_key = if descending { by_key.reverse() } else { by_key };
+            by_key.then_with(|| a.path.cmp(&b.path))
+        });
+        let total = items.len();
+        let page = items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        (total, page)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LibraryChange {
+    Added { item: WallpaperItem },
+    Modified { item: WallpaperItem },
+    Removed { path: String },
+}
+
+impl LibraryChange {
+    pub fn item_mut(&mut self) -> Option<&mut WallpaperItem> {
+        match self {
+            LibraryChange::Added { item } | LibraryChange::Modified { item } => Some(item),
+            LibraryChange::Removed { .. } => None,
+        }
+    }
+}
+
+/// Remembers which images exist so raw filesystem events can be reported as
+/// additions, modifications or removals.
+pub struct LibraryTracker {
+    library: Library,
+    known: HashSet<PathBuf>,
+}
+
+impl LibraryTracker {
+    /// `known` is the current listing, e.g. from the index.
+    pub fn new(library: Library, known: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            library,
+            known: known.into_iter().collect(),
+        }
+    }
+
+    pub fn apply(&mut self, touched: Vec<PathBuf>) -> Vec<LibraryChange> {
+        let mut changes = vec![];
+        for path in touched {
+            if path.is_dir() {
+                // A folder moved or copied in: everything inside is new.
+                for file in walk_media(&self.library, &path) {
+                    self.upsert(&file, &mut changes);
+                }
+            } else if path.exists() {
+                self.upsert(&path, &mut changes);
+            } else {
+                // The path may have been a file or a whole folder.
+                let gone: Vec<PathBuf> = self.known.iter().filter(|k| k.starts_with(&path)).cloned().collect();
+                for path in gone {
+                    self.known.remove(&path);
+                    changes.push(LibraryChange::Removed {
+                        path: path.to_string_lossy().to_string(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    fn upsert(&mut self, path: &Path, changes: &mut Vec<LibraryChange>) {
+        let Some(item) = self.library.item(path) else {
+            return;
+        };
+        if self.known.insert(PathBuf::from(&item.path)) {
+            changes.push(LibraryChange::Added { item });
+            return;
+        }
+        let reported = changes.iter().any(|c| match c {
+            LibraryChange::Added { item: seen } | LibraryChange::Modified { item: seen } => seen.path == item.path,
+            LibraryChange::Removed { .. } => false,
+        });
+        if !reported {
+            changes.push(LibraryChange::Modified { item });
+        }
+    }
+}
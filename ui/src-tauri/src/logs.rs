@@ -0,0 +1,279 @@
+// Native view of the CLI's logs.jsonl: filtered, paged queries and a tail that
+// picks up lines appended since the last read. Filters follow `listSystemLogs`
+// in `src/core/logs.ts`.
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::UiError;
+use crate::models::{LogEntry, CLI_SCHEMA_VERSION};
+
+pub const LOG_FILE: &str = "logs.jsonl";
+const LEVELS: [&str; 3] = ["info", "warn", "error"];
+
+pub fn log_path() -> Result<PathBuf, UiError> {
+    Ok(crate::paths::state_dir()?.join(LOG_FILE))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub source: Option<String>,
+    pub pack: Option<String>,
+    pub level: Option<String>,
+    /// Lowercased free-text query.
+    pub q: Option<String>,
+}
+
+fn clean(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+impl LogFilter {
+    pub fn new(source: Option<String>, pack: Option<String>, level: Option<String>, q: Option<String>) -> Self {
+        Self {
+            source: clean(source),
+            pack: clean(pack),
+            // Like the CLI, an unknown level means "any level".
+            level: clean(level).filter(|l| LEVELS.contains(&l.as_str())),
+            q: clean(q).map(|q| q.to_lowercase()),
+        }
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if self.source.as_ref().is_some_and(|s| entry.source.as_deref().unwrap_or("") != s) {
+            return false;
+        }
+        if self.pack.as_ref().is_some_and(|p| entry.pack.as_deref().unwrap_or("") != p) {
+            return false;
+        }
+        if self.level.as_ref().is_some_and(|l| &entry.level != l) {
+            return false;
+        }
+        let Some(q) = &self.q else {
+            return true;
+        };
+        let meta = entry.meta.as_ref().map(|m| serde_json::to_string(m).unwrap_or_default());
+        let blob = [
+            entry.source.as_deref().unwrap_or(""),
+            entry.pack.as_deref().unwrap_or(""),
+            &entry.action,
+            entry.message.as_deref().unwrap_or(""),
+            entry.url.as_deref().unwrap_or(""),
+            meta.as_deref().unwrap_or("{}"),
+        ]
+        .join(" ")
+        .to_lowercase();
+        blob.contains(q.as_str())
+    }
+}
+
+/// Position after the last entry of a page: its `ts`, and how many entries
+/// with that same `ts` were already returned. Sent to the UI as `"ts:seen"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub ts: u64,
+    pub seen: usize,
+}
+
+impl Cursor {
+    pub fn parse(raw: &str) -> Result<Self, UiError> {
+        let invalid = || UiError::invalid(format!("Invalid logs cursor: {raw}"));
+        let (ts, seen) = raw.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            ts: ts.parse().map_err(|_| invalid())?,
+            seen: seen.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}:{}", self.ts, self.seen))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+    pub schema_version: u64,
+    /// Newest first.
+    pub entries: Vec<LogEntry>,
+    /// Pass back as `before` to get the next (older) page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Valid entries in file order; broken lines are skipped like the CLI does.
+fn parse_lines(raw: &str) -> impl Iterator<Item = LogEntry> + '_ {
+    raw.lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+}
+
+pub fn read_all() -> Result<Vec<LogEntry>, UiError> {
+    let path = log_path()?;
+    match fs::read_to_string(&path) {
+        Ok(raw) => Ok(parse_lines(&raw).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(UiError::Host(format!("{}: {e}", path.display()))),
+    }
+}
+
+/// One page of `entries` (in file order), newest first, starting after `before`.
+pub fn page(entries: Vec<LogEntry>, filter: &LogFilter, limit: usize, before: Option<Cursor>) -> LogsPage {
+    // Stable sort, like the CLI's: on equal `ts` earlier lines stay first.
+    let mut ordered: Vec<LogEntry> = entries.into_iter().filter(|e| filter.matches(e)).collect();
+    ordered.sort_by_key(|e| std::cmp::Reverse(e.ts));
+
+    let start = match before {
+        None => 0,
+        Some(cursor) => {
+            let first_same = ordered.partition_point(|e| e.ts > cursor.ts);
+            let same = ordered[first_same..].iter().take_while(|e| e.ts == cursor.ts).count();
+            first_same + cursor.seen.min(same)
+        }
+    };
+    let end = (start + limit).min(ordered.len());
+    let next_cursor = (end < ordered.len() && end > start).then(|| {
+        let ts = ordered[end - 1].ts;
+        let seen = ordered[..end].iter().rev().take_while(|e| e.ts == ts).count();
+        Cursor { ts, seen }
+    });
+    LogsPage {
+        schema_version: CLI_SCHEMA_VERSION,
+        entries: ordered.drain(start..end).collect(),
+        next_cursor,
+    }
+}
+
+/// Follows logs.jsonl by byte offset. Past 5000 lines the CLI trims the file by
+/// rewriting it after every append, which can leave the length unchanged or
+/// larger while every offset has moved. So each read first checks that the
+/// last line we saw still ends at `offset`, and resyncs on it when it doesn't.
+#[derive(Debug, Default)]
+pub struct LogTail {
+    offset: u64,
+    last_line: Option<String>,
+}
+
+impl LogTail {
+    /// Starts at the current end of `path`.
+    pub fn at_end(path: &Path) -> Self {
+        let mut tail = Self::default();
+        let _ = tail.read_new(path);
+        tail
+    }
+
+    /// Entries appended to `path` since the previous call, in file order.
+    pub fn read_new(&mut self, path: &Path) -> Result<Vec<LogEntry>, UiError> {
+        let host_err = |e: std::io::Error| UiError::Host(format!("{}: {e}", path.display()));
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                *self = Self::default();
+                return Ok(vec![]);
+            }
+            Err(e) => return Err(host_err(e)),
+        };
+        let len = file.metadata().map_err(host_err)?.len();
+        let mut raw = String::new();
+        if len >= self.offset && self.still_anchored(&mut file).map_err(host_err)? {
+            file.seek(SeekFrom::Start(self.offset)).map_err(host_err)?;
+            file.read_to_string(&mut raw).map_err(host_err)?;
+        } else {
+            file.seek(SeekFrom::Start(0)).map_err(host_err)?;
+            file.read_to_string(&mut raw).map_err(host_err)?;
+            let resume = self
+                .last_line
+                .as_ref()
+                .and_then(|last| raw.rfind(&format!("{last}\n")).map(|at| at + last.len() + 1))
+                .unwrap_or(0);
+            raw.drain(..resume);
+            self.offset = resume as u64;
+        }
+        // A line still being written has no newline yet; leave it for next time.
+        let complete = raw.rfind('\n').map_or(0, |i| i + 1);
+        let fresh = &raw[..complete];
+        self.offset += complete as u64;
+        if let Some(last) = fresh.lines().rev().find(|l| !l.trim().is_empty()) {
+            self.last_line = Some(last.to_string());
+        }
+        Ok(parse_lines(fresh).collect())
+    }
+
+    /// Whether the bytes just before `offset` are still the last line we read.
+    fn still_anchored(&self, file: &mut File) -> std::io::Result<bool> {
+        let Some(last) = &self.last_line else {
+            return Ok(true);
+        };
+        let expected = format!("{last}\n");
+        let Some(start) = self.offset.checked_sub(expected.len() as u64) else {
+            return Ok(false);
+        };
+        let mut found = vec![0; expected.len()];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut found)?;
+        Ok(found == expected.as_bytes())
+    }
+}
+
+/// Live-follow state for the logs view: the filter the UI is showing and the
+/// tail position. Nothing is pushed while no filter is set.
+#[derive(Default)]
+pub struct LogFollower {
+    filter: Mutex<Option<LogFilter>>,
+    tail: Mutex<LogTail>,
+    /// The followed file; `None` is the CLI's logs.jsonl.
+    path: Option<PathBuf>,
+}
+
+impl LogFollower {
+    /// Follows `path` instead of the CLI's log.
+    #[cfg(test)]
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            ..Self::default()
+        }
+    }
+
+    fn path(&self) -> Result<PathBuf, UiError> {
+        self.path.clone().map_or_else(log_path, Ok)
+    }
+
+    pub fn follow(&self, filter: LogFilter) {
+        let Ok(path) = self.path() else {
+            return;
+        };
+        if let (Ok(mut current), Ok(mut tail)) = (self.filter.lock(), self.tail.lock()) {
+            *current = Some(filter);
+            *tail = LogTail::at_end(&path);
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut current) = self.filter.lock() {
+            *current = None;
+        }
+    }
+
+    /// New entries matching the followed filter, newest first.
+    pub fn poll(&self) -> Vec<LogEntry> {
+        let (Ok(filter), Ok(mut tail)) = (self.filter.lock(), self.tail.lock()) else {
+            return vec![];
+        };
+        let Some(filter) = filter.as_ref() else {
+            return vec![];
+        };
+        match self.path().and_then(|path| tail.read_new(&path)) {
+            Ok(entries) => entries.into_iter().rev().filter(|e| filter.matches(e)).collect(),
+            Err(err) => {
+                eprintln!("[kitowall-ui] log tail: {err}");
+                vec![]
+            }
+        }
+    }
+}
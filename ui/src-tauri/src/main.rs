@@ -1,30 +1,55 @@
+// Tauri commands mirror the frontend payloads one argument per field.
+#![allow(clippy::too_many_arguments)]
+
 use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::env;
-use std::sync::{Mutex, OnceLock};
-use std::io::Write;
-use std::os::unix::fs::PermissionsExt;
+use std::sync::{Mutex, OnceLock, RwLock};
 use base64::Engine as _;
-use thiserror::Error;
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
 
+mod audit;
+mod bridge;
+mod cache;
+mod config;
+mod dupes;
+mod error;
+mod host;
+mod index;
+mod library;
+mod logs;
+mod media;
+mod models;
+mod paths;
+mod runner;
+mod state;
+mod thumbs;
+mod tools;
+mod ui_config;
+mod watch;
+
+#[cfg(test)]
+mod tests;
+
+use error::UiError;
+use audit::{AuditLog, PendingCall};
+use bridge::{CallOptions, CliBridge, CliOutput, ExecError, LineSink, OutputStream};
+use config::PackConfig;
+use tools::{Compat, Handshake, ResolvedTools};
+use models::{
+    FavoritesPayload, HistoryPayload, ListPacksPayload, PackListPayload, SettingsPayload,
+    StatusPayload, TransitionSettings, WorkshopJob, WorkshopJobsPayload,
+};
+
 type Json = Value;
 const BOOTSTRAP_HOST_SH: &str = include_str!("../../../scripts/bootstrap-host.sh");
 
-#[derive(Debug, Error)]
-enum UiError {
-    #[error("command failed: {0}")]
-    CommandFailed(String),
-    #[error("invalid json: {0}")]
-    InvalidJson(String),
-}
-
-fn host_home_dir() -> Result<String, String> {
-    std::env::var("HOME").map_err(|e| format!("HOME is not available: {e}"))
+fn host_home_dir() -> Result<String, UiError> {
+    std::env::var("HOME").map_err(|e| UiError::Host(format!("HOME is not available: {e}")))
 }
 
 fn collect_nvm_bin_dirs(home: &str) -> Vec<String> {
@@ -43,7 +68,7 @@ fn collect_nvm_bin_dirs(home: &str) -> Vec<String> {
     dirs
 }
 
-fn host_user_path() -> Result<String, String> {
+fn host_user_path() -> Result<String, UiError> {
     let home = host_home_dir()?;
     let mut entries = vec![
         format!("{home}/.local/bin"),
@@ -78,17 +103,20 @@ fn host_user_path() -> Result<String, String> {
     Ok(unique.join(":"))
 }
 
-fn shell_output(cmdline: &str) -> Result<std::process::Output, String> {
-    let path = host_user_path()?;
-    host_aware_command("sh")
-        .env("PATH", path)
+fn shell_output(cmdline: &str) -> Result<std::process::Output, UiError> {
+    Ok(host_aware_command("sh")
         .args(["-lc", cmdline])
-        .output()
-        .map_err(|e| e.to_string())
+        .output()?)
 }
 
-fn resolve_host_bin_path(bin: &str) -> Result<Option<String>, String> {
-    let out = shell_output(&format!("command -v {bin} || true"))?;
+fn resolve_host_bin_path(bin: &str) -> Result<Option<String>, UiError> {
+    let program = ui_config::overridden_program(bin, ui_config::tool_override)?;
+    if Path::new(&program).is_absolute() {
+        return Ok(host::path_exists(Path::new(&program)).then_some(program));
+    }
+    let bin = program.as_str();
+
+    let out = shell_output(&format!("command -v {} || true", shell_words::quote(bin)))?;
     let by_path = String::from_utf8_lossy(&out.stdout).trim().to_string();
     if !by_path.is_empty() {
         return Ok(Some(by_path));
@@ -112,17 +140,31 @@ fn resolve_host_bin_path(bin: &str) -> Result<Option<String>, String> {
     };
 
     for candidate in fallback {
-        if PathBuf::from(&candidate).exists() {
+        if host::path_exists(Path::new(&candidate)) {
             return Ok(Some(candidate));
         }
     }
     Ok(None)
 }
 
+fn env_command_override(var: &str) -> Option<Vec<String>> {
+    let cmdline = std::env::var(var).ok().filter(|v| !v.trim().is_empty())?;
+    match ui_config::parse_command_line(var, &cmdline) {
+        Ok(parts) => Some(parts),
+        Err(e) => {
+            eprintln!("[kitowall-ui] ignoring {var}: {e}");
+            None
+        }
+    }
+}
+
 fn resolve_kitowall_cmd() -> Vec<String> {
-    // Optional override for advanced users.
-    if let Ok(cmd) = std::env::var("KITOWALL_CMD") {
-        return cmd.split_whitespace().map(|s| s.to_string()).collect();
+    // Optional overrides for advanced users: env first, then ui.toml.
+    if let Some(parts) = env_command_override("KITOWALL_CMD") {
+        return parts;
+    }
+    if let Some(parts) = ui_config::tool_override("kitowall") {
+        return parts;
     }
 
     if let Ok(Some(host_cli)) = resolve_host_bin_path("kitowall") {
@@ -145,95 +187,400 @@ fn resolve_kitowall_cmd() -> Vec<String> {
     vec!["kitowall".to_string()]
 }
 
-fn run_kitowall(args: &[&str]) -> Result<Json, UiError> {
-    let mut cmd_parts = resolve_kitowall_cmd();
+fn kitowall_command(tools: ResolvedTools, args: &[String]) -> Result<Command, UiError> {
+    if tools.handshake.status == Compat::Incompatible {
+        return Err(UiError::CliIncompatible(tools.handshake.message.unwrap_or_default()));
+    }
+    let mut cmd_parts = tools.kitowall;
     let base = cmd_parts.remove(0);
     if base == "__missing_kitowall_cli__" {
-        return Err(UiError::CommandFailed(
-            "kitowall CLI not found on host. Run scripts/bootstrap-host.sh or install globally (`npm i -g kitowall`), then retry.".to_string(),
-        ));
+        return Err(UiError::CliMissing("kitowall CLI".to_string()));
     }
-    let mut command = host_aware_command(&base);
+    let mut command = host_env_command(&base);
     if !cmd_parts.is_empty() {
         command.args(cmd_parts);
     }
     command.args(args);
-
-    let output = command.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            UiError::CommandFailed(
-                "kitowall CLI not found on host. Run scripts/bootstrap-host.sh or install globally (`npm i -g kitowall`).".to_string(),
-            )
-        } else {
-            UiError::CommandFailed(e.to_string())
+    Ok(command)
+}
+
+async fn exec_kitowall(
+    bridge: &CliBridge,
+    args: Vec<String>,
+    opts: CallOptions,
+    sink: Option<LineSink<'_>>,
+) -> Result<CliOutput, UiError> {
+    // Even a failed mutation may have written part of the config.
+    let mutation = (!cache::is_read_only(&args)).then(|| args.iter().take(2).cloned().collect::<Vec<_>>().join(" "));
+    let result = bridge.runner().run(bridge, args, opts, sink).await;
+    if let Some(reason) = mutation {
+        bridge.cache.invalidate(&reason);
+    }
+    result
+}
+
+/// `HostRunner` body: spawns the resolved kitowall CLI through the bridge.
+async fn exec_host_kitowall(
+    bridge: &CliBridge,
+    args: Vec<String>,
+    opts: CallOptions,
+    sink: Option<LineSink<'_>>,
+) -> Result<CliOutput, UiError> {
+    let command = kitowall_command(bridge.tools.current().await, &args)?;
+    let result = match sink {
+        Some(sink) => bridge.exec_streaming(command, opts, sink).await,
+        None => bridge.exec(command, opts).await,
+    };
+    let output = result.map_err(|e| match e {
+        ExecError::Spawn(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            UiError::CliMissing("kitowall CLI".to_string())
         }
+        other => other.into(),
     })?;
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if host::is_host_not_found(output.status.code(), &output.stderr) {
+        return Err(UiError::CliMissing("kitowall CLI".to_string()));
+    }
+    Ok(output)
+}
+
+fn failure_message(output: &CliOutput) -> String {
+    if !output.stderr.trim().is_empty() {
+        output.stderr.trim().to_string()
+    } else {
+        output.stdout.trim().to_string()
+    }
+}
 
+fn cli_failure(output: &CliOutput) -> UiError {
+    let message = failure_message(output);
+    match output.status.code() {
+        // Exit 2 is the CLI's "warning/action required" contract.
+        Some(2) => UiError::ActionRequired {
+            message,
+            code: None,
+            hint: None,
+        },
+        exit_code => UiError::CommandFailed {
+            message,
+            exit_code,
+            stderr: output.stderr.trim().to_string(),
+        },
+    }
+}
+
+async fn run_kitowall(bridge: &CliBridge, args: &[&str]) -> Result<Json, UiError> {
+    let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    run_kitowall_opts(bridge, owned, CallOptions::default()).await
+}
+
+const PROGRESS_EVENT: &str = "kitowall://progress";
+const CACHE_INVALIDATED_EVENT: &str = "kitowall://cache-invalidated";
+const STATE_CHANGED_EVENT: &str = "kitowall://state-changed";
+const LOGS_APPENDED_EVENT: &str = "kitowall://logs-appended";
+const LIBRARY_CHANGED_EVENT: &str = "kitowall://library-changed";
+const THUMBNAIL_READY_EVENT: &str = "kitowall://thumbnail-ready";
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgressEvent<'a> {
+    request_id: &'a str,
+    stream: &'static str,
+    line: &'a str,
+    percent: Option<f64>,
+}
+
+/// Picks the last `NN%` / `NN.N%` token out of a log line, if any.
+fn parse_progress_percent(line: &str) -> Option<f64> {
+    let bytes = line.as_bytes();
+    let mut found = None;
+    for (idx, b) in bytes.iter().enumerate() {
+        if *b != b'%' {
+            continue;
+        }
+        let start = line[..idx]
+            .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .map(|p| p + 1)
+            .unwrap_or(0);
+        if let Ok(v) = line[start..idx].parse::<f64>() {
+            if (0.0..=100.0).contains(&v) {
+                found = Some(v);
+            }
+        }
+    }
+    found
+}
+
+fn progress_sink<R: Runtime>(app: &AppHandle<R>) -> impl Fn(&str, OutputStream, &str) + Sync + '_ {
+    move |request_id: &str, stream: OutputStream, line: &str| {
+        let _ = app.emit(
+            PROGRESS_EVENT,
+            ProgressEvent {
+                request_id,
+                stream: stream.as_str(),
+                line,
+                percent: parse_progress_percent(line),
+            },
+        );
+    }
+}
+
+fn parse_kitowall_json(output: CliOutput) -> Result<Json, UiError> {
     // CLI contract: with --json, exit 0 (ok) and exit 2 (warning/action required)
     // are both valid machine-readable responses.
-    if let Ok(json) = serde_json::from_str::<Json>(&stdout) {
+    if let Ok(json) = serde_json::from_str::<Json>(&output.stdout) {
         return Ok(json);
     }
 
     if !output.status.success() {
-        let msg = if !stderr.trim().is_empty() {
-            stderr.trim().to_string()
-        } else {
-            stdout.trim().to_string()
-        };
-        return Err(UiError::CommandFailed(msg));
+        return Err(cli_failure(&output));
     }
 
-    Err(UiError::InvalidJson(stdout))
+    Err(UiError::InvalidJson(output.stdout))
 }
 
-fn run_kitowall_raw(args: &[&str]) -> Result<String, UiError> {
-    let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-    run_kitowall_raw_owned(owned)
+/// Passes `secrets` to the CLI as JSON on stdin (`--secrets-stdin`) so they never
+/// appear in argv, `ps` or the audit log.
+async fn run_kitowall_with_secrets(
+    bridge: &CliBridge,
+    args: &[&str],
+    secrets: Json,
+) -> Result<Json, UiError> {
+    if let Some(obj) = secrets.as_object() {
+        obj.values().filter_map(|v| v.as_str()).for_each(error::register_secret);
+    }
+    let mut owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    owned.push("--secrets-stdin".to_string());
+    run_kitowall_opts(bridge, owned, CallOptions::default().with_stdin(secrets.to_string())).await
 }
 
-fn run_kitowall_raw_owned(args: Vec<String>) -> Result<String, UiError> {
-    let mut cmd_parts = resolve_kitowall_cmd();
-    let base = cmd_parts.remove(0);
-    if base == "__missing_kitowall_cli__" {
-        return Err(UiError::CommandFailed(
-            "kitowall CLI not found on host. Run scripts/bootstrap-host.sh or install globally (`npm i -g kitowall`), then retry.".to_string(),
-        ));
+async fn run_kitowall_opts(bridge: &CliBridge, args: Vec<String>, opts: CallOptions) -> Result<Json, UiError> {
+    let output = exec_kitowall(bridge, args, opts, None).await?;
+    parse_kitowall_json(output)
+}
+
+/// JSON variant of `run_kitowall_raw_streaming`.
+async fn run_kitowall_streaming<R: Runtime>(
+    app: &AppHandle<R>,
+    bridge: &CliBridge,
+    args: Vec<String>,
+    opts: CallOptions,
+) -> Result<Json, UiError> {
+    let sink = progress_sink(app);
+    let output = exec_kitowall(bridge, args, opts, Some(&sink)).await?;
+    parse_kitowall_json(output)
+}
+
+/// Runs a read command and validates its output against the typed model `T`.
+/// Cacheable queries are served from `bridge.cache` while fresh.
+async fn query_kitowall<T: serde::de::DeserializeOwned>(bridge: &CliBridge, args: &[&str]) -> Result<T, UiError> {
+    let key: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let what = args.join(" ");
+    if !cache::is_cacheable(&key) {
+        return models::decode(&what, run_kitowall(bridge, args).await?);
     }
-    let mut command = host_aware_command(&base);
-    if !cmd_parts.is_empty() {
-        command.args(cmd_parts);
+    if let Some(json) = bridge.cache.get(&key) {
+        return models::decode(&what, json);
     }
-    command.args(&args);
+    let generation = bridge.cache.generation();
+    let json = run_kitowall(bridge, args).await?;
+    let value = models::decode(&what, json.clone())?;
+    bridge.cache.put(generation, key, json);
+    Ok(value)
+}
 
-    let output = command.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            UiError::CommandFailed(
-                "kitowall CLI not found on host. Run scripts/bootstrap-host.sh or install globally (`npm i -g kitowall`).".to_string(),
-            )
-        } else {
-            UiError::CommandFailed(e.to_string())
+async fn pack_list(bridge: &CliBridge) -> Result<PackListPayload, UiError> {
+    let list: PackListPayload = query_kitowall(bridge, &["pack", "list"]).await?;
+    register_pack_keys(&list);
+    Ok(list)
+}
+
+/// `pack list` straight from the CLI. Callers holding `lock_config` decide
+/// add vs update from it, so a cached list up to 5 s old is not good enough.
+async fn pack_list_uncached(bridge: &CliBridge) -> Result<PackListPayload, UiError> {
+    let list: PackListPayload = models::decode("pack list", run_kitowall(bridge, &["pack", "list"]).await?)?;
+    register_pack_keys(&list);
+    Ok(list)
+}
+
+/// Stored keys can surface in adapter errors (request URLs), so scrub them too.
+fn register_pack_keys(list: &PackListPayload) {
+    for (name, pack) in &list.packs {
+        match &pack.api_key {
+            Some(key) => error::register_named_secret(&pack_secret_name(name), key),
+            None => error::forget_named_secret(&pack_secret_name(name)),
         }
-    })?;
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    }
+}
 
+fn pack_secret_name(pack: &str) -> String {
+    format!("pack:{pack}")
+}
+
+/// Stores `key` for `pack` over stdin; the pack's previous key stops being masked.
+async fn set_pack_key(bridge: &CliBridge, pack: &str, key: &str) -> Result<Json, UiError> {
+    let out =
+        run_kitowall_with_secrets(bridge, &["pack", "set-key", pack], serde_json::json!({"apiKey": key})).await?;
+    error::register_named_secret(&pack_secret_name(pack), key);
+    Ok(out)
+}
+
+async fn run_kitowall_raw(bridge: &CliBridge, args: &[&str]) -> Result<String, UiError> {
+    let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    run_kitowall_raw_owned(bridge, owned, CallOptions::default()).await
+}
+
+async fn run_kitowall_raw_owned(bridge: &CliBridge, args: Vec<String>, opts: CallOptions) -> Result<String, UiError> {
+    let output = exec_kitowall(bridge, args, opts, None).await?;
     if !output.status.success() {
-        let msg = if !stderr.trim().is_empty() {
-            stderr.trim().to_string()
-        } else {
-            stdout.trim().to_string()
+        return Err(cli_failure(&output));
+    }
+    Ok(output.stdout)
+}
+
+/// Like `run_kitowall_raw_owned`, but emits every output line as a `kitowall://progress`
+/// event tagged with the call's request id.
+async fn run_kitowall_raw_streaming<R: Runtime>(
+    app: &AppHandle<R>,
+    bridge: &CliBridge,
+    args: Vec<String>,
+    opts: CallOptions,
+) -> Result<String, UiError> {
+    let sink = progress_sink(app);
+    let output = exec_kitowall(bridge, args, opts, Some(&sink)).await?;
+    if !output.status.success() {
+        return Err(cli_failure(&output));
+    }
+    Ok(output.stdout)
+}
+
+/// Re-resolves the kitowall/kitsune command lines and runs the `--version` handshake.
+async fn refresh_cli_tools(bridge: &CliBridge) -> ResolvedTools {
+    let (kitowall, kitsune) =
+        tauri::async_runtime::spawn_blocking(|| (resolve_kitowall_cmd(), resolve_kitsune_cmd()))
+            .await
+            .unwrap_or_else(|_| (vec!["kitowall".to_string()], vec!["kitsune".to_string()]));
+
+    let handshake = if kitowall[0] == "__missing_kitowall_cli__" {
+        Handshake::unavailable(UiError::CliMissing("kitowall CLI".to_string()).to_string())
+    } else {
+        let mut version_cmd = host_env_command(&kitowall[0]);
+        version_cmd.args(&kitowall[1..]).arg("--version");
+        let opts = CallOptions {
+            timeout: Some(std::time::Duration::from_secs(15)),
+            ..CallOptions::default()
         };
-        return Err(UiError::CommandFailed(msg));
+        match bridge.exec(version_cmd, opts).await {
+            Ok(out) if out.status.success() => Handshake::from_version_output(&out.stdout),
+            Ok(out) => Handshake::unavailable(failure_message(&out)),
+            Err(e) => Handshake::unavailable(UiError::from(e).to_string()),
+        }
+    };
+    if handshake.status != Compat::Ok {
+        if let Some(msg) = &handshake.message {
+            eprintln!("[kitowall-ui] CLI handshake: {msg}");
+        }
     }
 
-    Ok(stdout)
+    let tools = ResolvedTools { kitowall, kitsune, handshake };
+    bridge.tools.store(tools.clone());
+    tools
 }
 
 #[tauri::command]
-fn kitowall_preflight_status() -> Result<Json, String> {
+async fn kitowall_cli_info(bridge: State<'_, CliBridge>) -> Result<ResolvedTools, UiError> {
+    Ok(bridge.tools.current().await)
+}
+
+#[tauri::command]
+async fn kitowall_cli_refresh(bridge: State<'_, CliBridge>) -> Result<ResolvedTools, UiError> {
+    Ok(refresh_cli_tools(&bridge).await)
+}
+
+fn ui_config_json(config: &ui_config::UiConfig) -> Result<Json, UiError> {
+    let effective: serde_json::Map<String, Json> = ui_config::OVERRIDABLE_TOOLS
+        .iter()
+        .filter_map(|tool| {
+            let cmdline = config.tools.get(*tool)?;
+            let parts = ui_config::parse_command_line(tool, cmdline).ok()?;
+            Some((tool.to_string(), serde_json::json!(parts)))
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "ok": true,
+        "path": ui_config::config_path()?.to_string_lossy(),
+        "supported": ui_config::OVERRIDABLE_TOOLS,
+        "tools": config.tools,
+        "effective": effective
+    }))
+}
+
+#[tauri::command]
+fn kitowall_ui_config_get() -> Result<Json, UiError> {
+    ui_config_json(&ui_config::load()?)
+}
+
+/// Sets (or clears, with an empty `command`) the ui.toml override for one tool.
+#[tauri::command]
+async fn kitowall_ui_config_set(
+    bridge: State<'_, CliBridge>,
+    tool: String,
+    command: Option<String>,
+) -> Result<Json, UiError> {
+    let tool = tool.trim().to_string();
+    if !ui_config::OVERRIDABLE_TOOLS.contains(&tool.as_str()) {
+        return Err(UiError::invalid(format!(
+            "unknown tool {tool:?}; expected one of {}",
+            ui_config::OVERRIDABLE_TOOLS.join(", ")
+        )));
+    }
+
+    let mut config = ui_config::load()?;
+    match command.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()) {
+        Some(cmdline) => {
+            ui_config::parse_command_line(&tool, &cmdline)?;
+            config.tools.insert(tool.clone(), cmdline);
+        }
+        None => {
+            config.tools.remove(&tool);
+        }
+    }
+    ui_config::save(&config)?;
+    bridge.cache.invalidate("ui-config");
+
+    if tool == "kitowall" || tool == "kitsune" {
+        refresh_cli_tools(&bridge).await;
+    }
+    ui_config_json(&config)
+}
+
+#[tauri::command]
+fn kitowall_ui_audit(bridge: State<'_, CliBridge>, limit: Option<usize>) -> Result<Json, UiError> {
+    let entries = bridge.audit.entries(limit.unwrap_or(100));
+    Ok(serde_json::json!({
+        "ok": true,
+        "capacity": bridge.audit.capacity(),
+        "file": bridge.audit.file(),
+        "entries": entries
+    }))
+}
+
+#[tauri::command]
+fn kitowall_cancel(bridge: State<'_, CliBridge>, request_id: String) -> Result<Json, UiError> {
+    let id = request_id.trim();
+    if id.is_empty() {
+        return Err(UiError::invalid("request_id is required"));
+    }
+    let cancelled = bridge.cancel(id);
+    Ok(serde_json::json!({"ok": true, "requestId": id, "cancelled": cancelled}))
+}
+
+#[tauri::command]
+fn kitowall_inflight(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    Ok(serde_json::json!({"ok": true, "calls": bridge.inflight()}))
+}
+
+#[tauri::command]
+fn kitowall_preflight_status() -> Result<Json, UiError> {
     let checks = [
         ("kitowall", "kitowall", false),
         ("kitsune", "kitsune", false),
@@ -243,6 +590,8 @@ fn kitowall_preflight_status() -> Result<Json, String> {
         ("hyprctl", "hyprctl", false),
         ("mpvpaper", "mpvpaper", true),
         ("cava", "cava", false),
+        // Video posters and AVIF thumbnails; the gallery falls back to the originals.
+        ("ffmpeg", "ffmpeg", true),
     ];
 
     let mut deps: Vec<Json> = vec![];
@@ -259,13 +608,25 @@ fn kitowall_preflight_status() -> Result<Json, String> {
 
     Ok(serde_json::json!({
       "ok": true,
+      "host": host::mode(),
       "deps": deps
     }))
 }
 
+/// `kitowall_preflight_status` for async commands: it spawns a login shell per
+/// dependency, so it runs on the blocking pool.
+async fn preflight_status_off_runtime() -> Result<Json, UiError> {
+    tauri::async_runtime::spawn_blocking(kitowall_preflight_status)
+        .await
+        .map_err(|e| UiError::Host(e.to_string()))?
+}
+
 fn resolve_kitsune_cmd() -> Vec<String> {
-    if let Ok(cmd) = std::env::var("KITSUNE_CMD") {
-        return cmd.split_whitespace().map(|s| s.to_string()).collect();
+    if let Some(parts) = env_command_override("KITSUNE_CMD") {
+        return parts;
+    }
+    if let Some(parts) = ui_config::tool_override("kitsune") {
+        return parts;
     }
 
     if let Ok(home) = host_home_dir() {
@@ -288,81 +649,108 @@ fn resolve_kitsune_cmd() -> Vec<String> {
     vec!["kitsune".to_string()]
 }
 
-#[tauri::command]
-fn kitowall_preflight_install(namespace: Option<String>) -> Result<Json, String> {
-    let ns = namespace.unwrap_or_else(|| "kitowall".to_string());
-    let home = host_home_dir()?;
-    let tmp_script = PathBuf::from(format!("/tmp/kitowall-bootstrap-{}.sh", std::process::id()));
+/// Paths shown in the bootstrap report (snake_case, as the UI already reads them).
+fn preflight_paths() -> Result<Json, UiError> {
+    let paths = paths::resolve()?;
+    Ok(serde_json::json!({
+      "home": paths.home,
+      "local_bin": paths.local_bin,
+      "cargo_bin": paths.cargo_bin,
+      "kitowall_config": paths.config_dir,
+      "config_file": paths.config_file,
+      "rendercore_env": paths.rendercore_env
+    }))
+}
 
-    {
-        let mut f = fs::File::create(&tmp_script).map_err(|e| e.to_string())?;
-        f.write_all(BOOTSTRAP_HOST_SH.as_bytes()).map_err(|e| e.to_string())?;
-    }
-    let mut perms = fs::metadata(&tmp_script).map_err(|e| e.to_string())?.permissions();
-    perms.set_mode(0o755);
-    fs::set_permissions(&tmp_script, perms).map_err(|e| e.to_string())?;
-
-    let path = host_user_path()?;
-    let bootstrap_out = host_aware_command("bash")
-        .env("PATH", &path)
-        .env("HOME", &home)
-        .arg(tmp_script.to_string_lossy().to_string())
-        .output()
-        .map_err(|e| e.to_string())?;
-    let _ = fs::remove_file(&tmp_script);
+#[tauri::command]
+fn kitowall_paths() -> Result<paths::ResolvedPaths, UiError> {
+    paths::resolve()
+}
 
+#[tauri::command]
+async fn kitowall_preflight_install<R: Runtime>(
+    app: AppHandle<R>,
+    bridge: State<'_, CliBridge>,
+    namespace: Option<String>,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Json, UiError> {
+    let ns = namespace.unwrap_or_else(|| "kitowall".to_string());
+    // Passed inline rather than via a temp file: a sandboxed UI's /tmp is not the host's.
+    let mut bootstrap = host_aware_command("bash");
+    bootstrap.args(["-c", BOOTSTRAP_HOST_SH, "kitowall-bootstrap"]);
+    let opts = CallOptions::from_ui(request_id, timeout_ms).long_running();
+    let sink = progress_sink(&app);
+    let bootstrap_out = bridge.exec_streaming(bootstrap, opts, &sink).await;
+    let bootstrap_out = bootstrap_out.map_err(UiError::from)?;
+
+    let request_id = bootstrap_out.request_id.clone();
     let mut logs = String::new();
-    logs.push_str(&String::from_utf8_lossy(&bootstrap_out.stdout));
-    logs.push_str(&String::from_utf8_lossy(&bootstrap_out.stderr));
+    logs.push_str(&bootstrap_out.stdout);
+    logs.push_str(&bootstrap_out.stderr);
 
-    let deps = kitowall_preflight_status()?;
+    let deps = preflight_status_off_runtime().await?;
     if !bootstrap_out.status.success() {
         return Ok(serde_json::json!({
             "ok": false,
             "step": "bootstrap-host",
+            "requestId": request_id,
             "code": bootstrap_out.status.code().unwrap_or(1),
             "namespace": ns,
             "logs": logs,
             "deps": deps,
-            "paths": {
-              "home": home,
-              "local_bin": format!("{}/.local/bin", home),
-              "cargo_bin": format!("{}/.cargo/bin", home),
-              "kitowall_config": format!("{}/.config/kitowall", home),
-              "rendercore_env": format!("{}/.config/kitsune-rendercore/env", home)
-            }
+            "paths": preflight_paths()?
         }));
     }
 
-    let _ = run_kitowall(&["init", "--namespace", ns.as_str(), "--apply", "--force", "--json"]);
-    let _ = run_kitowall_raw(&["install-systemd", "--every", "600s"]);
-    let deps_after = kitowall_preflight_status()?;
+    // Bootstrap may have installed or moved the CLI; drop the cached resolution.
+    refresh_cli_tools(&bridge).await;
+    {
+        let _config = bridge.lock_config().await;
+        let _ = run_kitowall(&bridge, &["init", "--namespace", ns.as_str(), "--apply", "--force", "--json"]).await;
+    }
+    let _ = run_kitowall_raw_streaming(
+        &app,
+        &bridge,
+        vec!["install-systemd".into(), "--every".into(), "600s".into()],
+        CallOptions::default(),
+    )
+    .await;
+    let deps_after = preflight_status_off_runtime().await?;
 
     Ok(serde_json::json!({
       "ok": true,
       "step": "bootstrap-host",
+      "requestId": request_id,
       "namespace": ns,
       "logs": logs,
       "deps": deps_after,
-      "paths": {
-        "home": home,
-        "local_bin": format!("{}/.local/bin", home),
-        "cargo_bin": format!("{}/.cargo/bin", home),
-        "kitowall_config": format!("{}/.config/kitowall", home),
-        "rendercore_env": format!("{}/.config/kitsune-rendercore/env", home)
-      }
+      "paths": preflight_paths()?
     }))
 }
 
+/// Command for a host tool, honoring its ui.toml override when one is set.
 fn host_aware_command(base: &str) -> Command {
-    let mut cmd = Command::new(base);
+    match ui_config::tool_override(base) {
+        Some(parts) => {
+            let mut cmd = host_env_command(&parts[0]);
+            cmd.args(&parts[1..]);
+            cmd
+        }
+        None => host_env_command(base),
+    }
+}
+
+/// Runs `program` as-is on the host with the user's PATH and HOME.
+fn host_env_command(program: &str) -> Command {
+    let mut env = Vec::new();
     if let Ok(path) = host_user_path() {
-        cmd.env("PATH", path);
+        env.push(("PATH", path));
     }
     if let Ok(home) = host_home_dir() {
-        cmd.env("HOME", home);
+        env.push(("HOME", home));
     }
-    cmd
+    host::command(program, &env)
 }
 
 static NATIVE_PREVIEW_CHILD: OnceLock<Mutex<Option<Child>>> = OnceLock::new();
@@ -371,11 +759,11 @@ fn native_preview_slot() -> &'static Mutex<Option<Child>> {
     NATIVE_PREVIEW_CHILD.get_or_init(|| Mutex::new(None))
 }
 
-fn stop_native_preview_process() -> Result<(), String> {
+fn stop_native_preview_process() -> Result<(), UiError> {
     let slot = native_preview_slot();
     let mut guard = slot
         .lock()
-        .map_err(|_| "native preview lock poisoned".to_string())?;
+        .map_err(|_| UiError::Host("native preview lock poisoned".to_string()))?;
     if let Some(mut child) = guard.take() {
         let _ = child.kill();
         let _ = child.wait();
@@ -384,16 +772,16 @@ fn stop_native_preview_process() -> Result<(), String> {
 }
 
 #[tauri::command]
-fn kitowall_native_preview_stop() -> Result<Json, String> {
+fn kitowall_native_preview_stop() -> Result<Json, UiError> {
     stop_native_preview_process()?;
     Ok(serde_json::json!({"ok": true}))
 }
 
 #[tauri::command]
-fn kitowall_native_preview_start(source: String) -> Result<Json, String> {
+fn kitowall_native_preview_start(source: String) -> Result<Json, UiError> {
     let src = source.trim();
     if src.is_empty() {
-        return Err("source is required".to_string());
+        return Err(UiError::invalid("source is required"));
     }
 
     stop_native_preview_process()?;
@@ -414,12 +802,12 @@ fn kitowall_native_preview_start(source: String) -> Result<Json, String> {
 
     let child = cmd
         .spawn()
-        .map_err(|e| format!("failed to start mpv: {e}"))?;
+        .map_err(|e| UiError::Host(format!("failed to start mpv: {e}")))?;
     let pid = child.id();
     let slot = native_preview_slot();
     let mut guard = slot
         .lock()
-        .map_err(|_| "native preview lock poisoned".to_string())?;
+        .map_err(|_| UiError::Host("native preview lock poisoned".to_string()))?;
     *guard = Some(child);
 
     Ok(serde_json::json!({
@@ -429,15 +817,31 @@ fn kitowall_native_preview_start(source: String) -> Result<Json, String> {
     }))
 }
 
-fn systemctl_show(unit: &str, props: &[&str]) -> Result<Json, String> {
+fn systemctl_show(audit: &AuditLog, unit: &str, props: &[&str]) -> Result<Json, UiError> {
     let mut cmd = host_aware_command("systemctl");
     cmd.args(["--user", "show", unit, "--no-pager"]);
     for p in props {
         cmd.arg("-p").arg(p);
     }
-    let out = cmd.output().map_err(|e| format!("systemctl error: {}", e))?;
+    let call = PendingCall::start(&format!("systemctl-{unit}"), &cmd);
+    let out = match cmd.output() {
+        Ok(out) => {
+            let outcome = if out.status.success() { "ok" } else { "failed" };
+            audit.record(call.finish(outcome, out.status.code(), &String::from_utf8_lossy(&out.stderr)));
+            out
+        }
+        Err(e) => {
+            audit.record(call.finish("error", None, &e.to_string()));
+            return Err(UiError::Host(format!("systemctl error: {}", e)));
+        }
+    };
     if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(UiError::CommandFailed {
+            message: stderr.clone(),
+            exit_code: out.status.code(),
+            stderr,
+        });
     }
     let text = String::from_utf8_lossy(&out.stdout).to_string();
     let mut obj = serde_json::Map::new();
@@ -450,18 +854,18 @@ fn systemctl_show(unit: &str, props: &[&str]) -> Result<Json, String> {
 }
 
 #[tauri::command]
-fn kitowall_check(namespace: Option<String>) -> Result<Json, String> {
+async fn kitowall_check(bridge: State<'_, CliBridge>, namespace: Option<String>) -> Result<Json, UiError> {
     let ns = namespace.unwrap_or_else(|| "kitowall".to_string());
-    run_kitowall(&["check", "--namespace", &ns, "--json"]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["check", "--namespace", &ns, "--json"]).await
 }
 
 #[tauri::command]
-fn kitowall_status() -> Result<Json, String> {
-    run_kitowall(&["status"]).map_err(|e| e.to_string())
+async fn kitowall_status(bridge: State<'_, CliBridge>) -> Result<StatusPayload, UiError> {
+    query_kitowall(&bridge, &["status"]).await
 }
 
 #[tauri::command]
-fn kitowall_next(namespace: Option<String>, force: Option<bool>, pack: Option<String>) -> Result<Json, String> {
+async fn kitowall_next(bridge: State<'_, CliBridge>, namespace: Option<String>, force: Option<bool>, pack: Option<String>) -> Result<Json, UiError> {
     let ns = namespace.unwrap_or_else(|| "kitowall".to_string());
     let mut args: Vec<String> = vec![
         "next".to_string(),
@@ -479,42 +883,51 @@ fn kitowall_next(namespace: Option<String>, force: Option<bool>, pack: Option<St
         }
     }
     let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&arg_refs).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &arg_refs).await
 }
 
 #[tauri::command]
-fn kitowall_init_apply(namespace: Option<String>) -> Result<Json, String> {
+async fn kitowall_init_apply(bridge: State<'_, CliBridge>, namespace: Option<String>) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let ns = namespace.unwrap_or_else(|| "kitowall".to_string());
-    run_kitowall(&["init", "--namespace", &ns, "--apply", "--force", "--json"]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["init", "--namespace", &ns, "--apply", "--force", "--json"]).await
 }
 
 #[tauri::command]
-fn kitowall_hydrate_pack(name: String, count: u32) -> Result<Json, String> {
-    let count_str = count.to_string();
-    run_kitowall(&["hydrate-pack", &name, "--count", &count_str]).map_err(|e| e.to_string())
+async fn kitowall_hydrate_pack<R: Runtime>(
+    app: AppHandle<R>,
+    bridge: State<'_, CliBridge>,
+    name: String,
+    count: u32,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Json, UiError> {
+    let args: Vec<String> = vec!["hydrate-pack".into(), name, "--count".into(), count.to_string()];
+    let opts = CallOptions::from_ui(request_id, timeout_ms).long_running();
+    run_kitowall_streaming(&app, &bridge, args, opts).await
 }
 
 #[tauri::command]
-fn kitowall_cache_prune() -> Result<Json, String> {
-    run_kitowall(&["cache-prune-hard"]).map_err(|e| e.to_string())
+async fn kitowall_cache_prune(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["cache-prune-hard"]).await
 }
 
 #[tauri::command]
-fn kitowall_cache_prune_pack(name: String) -> Result<Json, String> {
-    run_kitowall(&["cache-prune-pack-hard", &name]).map_err(|e| e.to_string())
+async fn kitowall_cache_prune_pack(bridge: State<'_, CliBridge>, name: String) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["cache-prune-pack-hard", &name]).await
 }
 
 #[tauri::command]
-fn kitowall_list_packs() -> Result<Json, String> {
-    run_kitowall(&["list-packs"]).map_err(|e| e.to_string())
+async fn kitowall_list_packs(bridge: State<'_, CliBridge>) -> Result<ListPacksPayload, UiError> {
+    query_kitowall(&bridge, &["list-packs"]).await
 }
 
 #[tauri::command]
-fn kitowall_list_pack_folders() -> Result<Json, String> {
-    let dir = resolve_download_root()?;
+fn kitowall_list_pack_folders() -> Result<Json, UiError> {
+    let dir = config::load()?.download_root()?;
     let mut names: Vec<String> = vec![];
     if dir.exists() {
-        let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+        let entries = fs::read_dir(dir)?;
         for entry in entries.flatten() {
             if entry.path().is_dir() {
                 if let Some(name) = entry.file_name().to_str() {
@@ -527,42 +940,8 @@ fn kitowall_list_pack_folders() -> Result<Json, String> {
     Ok(serde_json::json!({ "folders": names }))
 }
 
-fn is_image_ext(path: &PathBuf) -> bool {
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase();
-    matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp" | "bmp" | "gif" | "avif")
-}
-
-fn resolve_download_root() -> Result<PathBuf, String> {
-    let home = std::env::var("HOME").map_err(|e| e.to_string())?;
-    let default_root = PathBuf::from(&home).join("Pictures").join("Wallpapers");
-    let config_path = PathBuf::from(&home).join(".config").join("kitowall").join("config.json");
-    if !config_path.exists() {
-        return Ok(default_root);
-    }
-
-    let raw = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-    let json: Json = serde_json::from_str(&raw).map_err(|e| format!("invalid config.json: {}", e))?;
-    let dir = json
-        .get("cache")
-        .and_then(|v| v.get("downloadDir"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("~/Pictures/Wallpapers");
-
-    if dir == "~" {
-        return Ok(PathBuf::from(home));
-    }
-    if let Some(rest) = dir.strip_prefix("~/") {
-        return Ok(PathBuf::from(home).join(rest));
-    }
-    Ok(PathBuf::from(dir))
-}
-
-fn expand_tilde_path(input: &str) -> Result<PathBuf, String> {
-    let home = std::env::var("HOME").map_err(|e| e.to_string())?;
+fn expand_tilde_path(input: &str) -> Result<PathBuf, UiError> {
+    let home = host_home_dir()?;
     if input == "~" {
         return Ok(PathBuf::from(home));
     }
@@ -572,160 +951,188 @@ fn expand_tilde_path(input: &str) -> Result<PathBuf, String> {
     Ok(PathBuf::from(input))
 }
 
-fn resolve_local_pack_roots() -> Result<Vec<(PathBuf, String)>, String> {
-    let home = std::env::var("HOME").map_err(|e| e.to_string())?;
-    let config_path = PathBuf::from(&home).join(".config").join("kitowall").join("config.json");
-    if !config_path.exists() {
-        return Ok(vec![]);
-    }
-
-    let raw = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-    let json: Json = serde_json::from_str(&raw).map_err(|e| format!("invalid config.json: {}", e))?;
-    let packs = json.get("packs").and_then(|v| v.as_object()).cloned().unwrap_or_default();
-    let mut roots: Vec<(PathBuf, String)> = vec![];
+/// A page of the wallpaper library. Filtering and sorting happen here so the
+/// webview only ever holds the page it shows; `total` counts every match,
+/// `packs` lists every pack, for the filter, and `unreadable` the paths the
+/// scan had to skip.
+#[tauri::command]
+async fn kitowall_wallpapers_list<R: Runtime>(
+    app: AppHandle<R>,
+    query: Option<library::LibraryQuery>,
+) -> Result<Json, UiError> {
+    let query = query.unwrap_or_default();
+    if query.limit == Some(0) {
+        return Err(UiError::invalid("Invalid limit: 0"));
+    }
+    // Without a live watcher (or on first use) the listing walks the roots.
+    tauri::async_runtime::spawn_blocking(move || {
+        let index = app.state::<index::SharedIndex>();
+        let thumbnails = app.state::<thumbs::Thumbnails>();
+        let library = library::Library::load()?;
+        let items = index.list(&library)?;
+        thumbnails.retain(&items);
+        let library_total = items.len();
+        let packs: BTreeSet<String> = items.iter().map(|item| item.pack.clone()).collect();
+        let favorites: HashSet<String> = if query.favorites_only {
+            state::load_favorites()?.into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+        let (total, mut page) = query.apply(items, &favorites);
+        thumbnails.attach(&mut page);
+        Ok(serde_json::json!({
+            "ok": true,
+            "root": library.download_root,
+            "libraryTotal": library_total,
+            "total": total,
+            "offset": query.offset,
+            "packs": packs,
+            "items": page,
+            "unreadable": index.unreadable()
+        }))
+    })
+    .await
+    .map_err(|e| UiError::Host(e.to_string()))?
+}
 
-    for (pack_name, pack) in packs {
-        let is_local = pack.get("type").and_then(|v| v.as_str()) == Some("local");
-        if !is_local {
-            continue;
-        }
-        let paths_value = pack.get("paths");
-        match paths_value {
-            Some(Value::Array(items)) => {
-                for item in items {
-                    if let Some(p) = item.as_str() {
-                        let expanded = expand_tilde_path(p)?;
-                        roots.push((expanded, pack_name.clone()));
-                    }
-                }
-            }
-            Some(Value::String(single)) => {
-                let expanded = expand_tilde_path(single)?;
-                roots.push((expanded, pack_name.clone()));
-            }
-            _ => {}
-        }
+/// One library image with its metadata. Unlike the listing, this decodes the
+/// image for its dominant color when no thumbnail has been made yet.
+#[tauri::command]
+fn kitowall_wallpaper_info(
+    thumbnails: State<'_, thumbs::Thumbnails>,
+    path: String,
+) -> Result<library::WallpaperItem, UiError> {
+    let raw = path.trim();
+    if raw.is_empty() {
+        return Err(UiError::invalid("path is required"));
     }
-
-    Ok(roots)
+    let library = library::Library::load()?;
+    let mut item = library
+        .item(Path::new(raw))
+        .ok_or_else(|| UiError::invalid(format!("not in the wallpaper library: {raw}")))?;
+    thumbnails.attach(std::iter::once(&mut item));
+    if item.dominant_color.is_none() && item.kind != library::MediaKind::Video {
+        item.dominant_color = thumbs::color_of(Path::new(&item.path));
+    }
+    Ok(item)
 }
 
+/// Sets one library item as `monitor`'s wallpaper. Images and animations go to
+/// swww with the configured transition; videos, which swww can't play, go to
+/// the live backend through `kitowall live apply-file`.
 #[tauri::command]
-fn kitowall_wallpapers_list() -> Result<Json, String> {
-    let root = resolve_download_root()?;
-    let local_roots = resolve_local_pack_roots()?;
-    let mut items: Vec<Json> = vec![];
-    let mut roots_to_scan: Vec<(PathBuf, Option<String>)> = vec![];
-
-    if root.exists() {
-        roots_to_scan.push((fs::canonicalize(&root).map_err(|e| e.to_string())?, None));
+async fn kitowall_wallpaper_apply<R: Runtime>(app: AppHandle<R>, path: String, monitor: String) -> Result<Json, UiError> {
+    let monitor = monitor.trim().to_string();
+    if monitor.is_empty() {
+        return Err(UiError::invalid("monitor is required"));
     }
-    for (p, pack_name) in local_roots {
-        if p.exists() {
-            roots_to_scan.push((fs::canonicalize(&p).map_err(|e| e.to_string())?, Some(pack_name)));
+    let raw = path.trim();
+    let item = library::Library::load()?
+        .item(Path::new(raw))
+        .ok_or_else(|| UiError::invalid(format!("not in the wallpaper library: {raw}")))?;
+    if item.kind == library::MediaKind::Video {
+        let bridge = app.state::<CliBridge>();
+        run_kitowall(&bridge, &["live", "apply-file", &item.path, "--monitor", &monitor]).await?;
+        return Ok(serde_json::json!({"ok": true, "backend": "live", "path": item.path, "monitor": monitor}));
+    }
+    let transition = config::load()?.transition;
+    tauri::async_runtime::spawn_blocking(move || {
+        swww_img(&app.state::<CliBridge>().audit, &monitor, &item.path, &transition)?;
+        Ok(serde_json::json!({"ok": true, "backend": "swww", "path": item.path, "monitor": monitor}))
+    })
+    .await
+    .map_err(|e| UiError::Host(e.to_string()))?
+}
+
+/// `swww img` on one output, with the same arguments as the CLI's swww backend.
+fn swww_img(audit: &AuditLog, output: &str, path: &str, transition: &TransitionSettings) -> Result<(), UiError> {
+    let mut cmd = host_aware_command("swww");
+    cmd.args(["img", "--namespace", "kitowall", "-o", output, path])
+        .args(["--transition-type", &transition.kind])
+        .args(["--transition-fps", &transition.fps.to_string()])
+        .args(["--transition-duration", &transition.duration.to_string()]);
+    if let Some(angle) = transition.angle {
+        cmd.args(["--transition-angle", &angle.to_string()]);
+    }
+    if let Some(pos) = &transition.pos {
+        cmd.args(["--transition-pos", pos]);
+    }
+    let call = PendingCall::start("swww-img", &cmd);
+    let out = match cmd.output() {
+        Ok(out) => {
+            let outcome = if out.status.success() { "ok" } else { "failed" };
+            audit.record(call.finish(outcome, out.status.code(), &String::from_utf8_lossy(&out.stderr)));
+            out
+        }
+        Err(e) => {
+            audit.record(call.finish("error", None, &e.to_string()));
+            return Err(UiError::Host(format!("failed to run swww: {e}")));
         }
+    };
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(UiError::CommandFailed {
+            message: stderr.clone(),
+            exit_code: out.status.code(),
+            stderr,
+        });
     }
+    Ok(())
+}
 
-    if roots_to_scan.is_empty() {
-        return Ok(serde_json::json!({
-            "ok": true,
-            "root": root,
-            "items": items
-        }));
-    }
+/// Groups near-identical images across the library (see `dupes`). The first
+/// run makes any missing thumbnails, so it runs off the main thread.
+#[tauri::command]
+async fn kitowall_duplicates_find<R: Runtime>(app: AppHandle<R>, max_distance: Option<u32>) -> Result<Json, UiError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let library = library::Library::load()?;
+        // Video posters would pair a clip with a still of itself.
+        let items: Vec<_> = app
+            .state::<index::SharedIndex>()
+            .list(&library)?
+            .into_iter()
+            .filter(|item| item.kind != library::MediaKind::Video)
+            .collect();
+        let thumbnails = app.state::<thumbs::Thumbnails>();
+        let hashes = thumbnails.dhashes(&items);
+        let hashed = items.into_iter().zip(hashes).filter_map(|(item, hash)| Some((item, hash?)));
+        let mut groups = dupes::group(hashed.collect(), max_distance.unwrap_or(dupes::DEFAULT_MAX_DISTANCE));
+        thumbnails.attach(groups.iter_mut().flat_map(|g| g.items.iter_mut()));
+        Ok(serde_json::json!({"ok": true, "groups": groups}))
+    })
+    .await
+    .map_err(|e| UiError::Host(e.to_string()))?
+}
 
-    let mut stack: Vec<(PathBuf, usize)> = vec![];
-    for (idx, (base, _)) in roots_to_scan.iter().enumerate() {
-        stack.push((base.clone(), idx));
+/// Removes the given library images, to the trash unless `permanent`. Every
+/// path is attempted; failures are reported per path.
+#[tauri::command]
+fn kitowall_duplicates_remove(paths: Vec<String>, permanent: Option<bool>) -> Result<Json, UiError> {
+    if paths.is_empty() {
+        return Err(UiError::invalid("paths is required"));
+    }
+    let library = library::Library::load()?;
+    let mut removed = vec![];
+    let mut failed = vec![];
+    for path in paths {
+        match dupes::remove(&library, path.trim(), permanent.unwrap_or(false)) {
+            Ok(()) => removed.push(path),
+            Err(err) => failed.push(serde_json::json!({"path": path, "error": err.to_string()})),
+        }
     }
-    let mut seen_paths: HashSet<String> = HashSet::new();
-
-    while let Some((dir, root_idx)) = stack.pop() {
-        let entries = match fs::read_dir(&dir) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                stack.push((path, root_idx));
-                continue;
-            }
-            if !is_image_ext(&path) {
-                continue;
-            }
-
-            let abs = match fs::canonicalize(&path) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            let base_root = &roots_to_scan[root_idx].0;
-            let rel = match abs.strip_prefix(base_root) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            let abs_str = abs.to_string_lossy().to_string();
-            if seen_paths.contains(&abs_str) {
-                continue;
-            }
-            seen_paths.insert(abs_str.clone());
-
-            let rel_parts: Vec<String> = rel
-                .iter()
-                .map(|s| s.to_string_lossy().to_string())
-                .collect();
-            let pack = match &roots_to_scan[root_idx].1 {
-                Some(local_pack_name) => local_pack_name.clone(),
-                None => rel_parts
-                    .first()
-                    .cloned()
-                    .unwrap_or_else(|| "root".to_string())
-            };
-            let file_name = abs
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_string();
-
-            let modified_ms = fs::metadata(&abs)
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_millis() as u64)
-                .unwrap_or(0);
-
-            items.push(serde_json::json!({
-                "path": abs_str,
-                "pack": pack,
-                "fileName": file_name,
-                "modifiedMs": modified_ms
-            }));
-        }
-    }
-
-    items.sort_by(|a, b| {
-        let am = a.get("modifiedMs").and_then(|v| v.as_u64()).unwrap_or(0);
-        let bm = b.get("modifiedMs").and_then(|v| v.as_u64()).unwrap_or(0);
-        bm.cmp(&am)
-    });
-
-    Ok(serde_json::json!({
-        "ok": true,
-        "root": root,
-        "items": items
-    }))
+    Ok(serde_json::json!({"ok": failed.is_empty(), "removed": removed, "failed": failed}))
 }
 
 #[tauri::command]
-fn kitowall_open_pack_folder(name: String) -> Result<Json, String> {
-    let root = resolve_download_root()?;
+fn kitowall_open_pack_folder(name: String) -> Result<Json, UiError> {
+    let root = config::load()?.download_root()?;
     if !root.exists() {
-        return Err(format!("Wallpaper root not found: {}", root.display()));
+        return Err(UiError::Host(format!("Wallpaper root not found: {}", root.display())));
     }
 
     let target_norm = name.trim().to_lowercase().replace([' ', '_'], "-");
     let mut chosen: Option<PathBuf> = None;
-    let entries = fs::read_dir(&root).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(&root)?;
     for entry in entries.flatten() {
         let p = entry.path();
         if !p.is_dir() {
@@ -742,27 +1149,28 @@ fn kitowall_open_pack_folder(name: String) -> Result<Json, String> {
 
     let path = chosen.unwrap_or_else(|| root.join(&name));
     if !path.exists() {
-        return Err(format!("Pack folder not found: {}", path.display()));
+        return Err(UiError::Host(format!("Pack folder not found: {}", path.display())));
     }
 
     let status = host_aware_command("xdg-open")
         .arg(&path)
         .status()
-        .map_err(|e| format!("failed to run xdg-open: {}", e))?;
+        .map_err(|e| UiError::Host(format!("failed to run xdg-open: {}", e)))?;
     if !status.success() {
-        return Err(format!("xdg-open failed with status: {}", status));
+        return Err(UiError::Host(format!("xdg-open failed with status: {}", status)));
     }
 
     Ok(serde_json::json!({ "ok": true, "path": path }))
 }
 
 #[tauri::command]
-fn kitowall_settings_get() -> Result<Json, String> {
-    run_kitowall(&["settings", "get"]).map_err(|e| e.to_string())
+fn kitowall_settings_get() -> Result<SettingsPayload, UiError> {
+    Ok(config::load()?.settings(&state::load_state()?))
 }
 
 #[tauri::command]
-fn kitowall_settings_set(
+async fn kitowall_settings_set(
+    bridge: State<'_, CliBridge>,
     mode: Option<String>,
     rotation_interval_sec: Option<u32>,
     transition_type: Option<String>,
@@ -770,7 +1178,8 @@ fn kitowall_settings_set(
     transition_duration: Option<f64>,
     transition_angle: Option<f64>,
     transition_pos: Option<String>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let mut args: Vec<String> = vec!["settings".into(), "set".into()];
     if let Some(v) = mode { args.push("--mode".into()); args.push(v); }
     if let Some(v) = rotation_interval_sec { args.push("--rotation-interval-sec".into()); args.push(v.to_string()); }
@@ -780,38 +1189,41 @@ fn kitowall_settings_set(
     if let Some(v) = transition_angle { args.push("--transition-angle".into()); args.push(v.to_string()); }
     if let Some(v) = transition_pos { args.push("--transition-pos".into()); args.push(v); }
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &refs).await
 }
 
 #[tauri::command]
-fn kitowall_history_list(limit: Option<u32>) -> Result<Json, String> {
-    let mut args: Vec<String> = vec!["history".into()];
-    if let Some(v) = limit {
-        args.push("--limit".into());
-        args.push(v.to_string());
+fn kitowall_history_list(limit: Option<u32>) -> Result<HistoryPayload, UiError> {
+    let limit = limit.unwrap_or(200);
+    if limit == 0 {
+        return Err(UiError::invalid("Invalid limit: 0"));
     }
-    let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    state::load_history(limit as usize)
 }
 
 #[tauri::command]
-fn kitowall_history_clear() -> Result<Json, String> {
-    run_kitowall(&["history", "clear"]).map_err(|e| e.to_string())
+fn kitowall_state_get() -> Result<state::RuntimeState, UiError> {
+    state::load_state()
 }
 
 #[tauri::command]
-fn kitowall_favorites_list() -> Result<Json, String> {
-    run_kitowall(&["favorites"]).map_err(|e| e.to_string())
+async fn kitowall_history_clear(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["history", "clear"]).await
 }
 
 #[tauri::command]
-fn kitowall_favorite_add(path: String) -> Result<Json, String> {
-    run_kitowall(&["favorite", "add", &path]).map_err(|e| e.to_string())
+async fn kitowall_favorites_list(bridge: State<'_, CliBridge>) -> Result<FavoritesPayload, UiError> {
+    query_kitowall(&bridge, &["favorites"]).await
 }
 
 #[tauri::command]
-fn kitowall_favorite_remove(path: String) -> Result<Json, String> {
-    run_kitowall(&["favorite", "remove", &path]).map_err(|e| e.to_string())
+async fn kitowall_favorite_add(bridge: State<'_, CliBridge>, path: String) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["favorite", "add", &path]).await
+}
+
+#[tauri::command]
+async fn kitowall_favorite_remove(bridge: State<'_, CliBridge>, path: String) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["favorite", "remove", &path]).await
 }
 
 #[tauri::command]
@@ -821,56 +1233,49 @@ fn kitowall_logs(
     pack: Option<String>,
     level: Option<String>,
     q: Option<String>,
-) -> Result<Json, String> {
-    let mut args: Vec<String> = vec!["logs".into()];
-    if let Some(v) = limit {
-        args.push("--limit".into());
-        args.push(v.to_string());
-    }
-    if let Some(v) = source {
-        let s = v.trim().to_string();
-        if !s.is_empty() {
-            args.push("--source".into());
-            args.push(s);
-        }
-    }
-    if let Some(v) = pack {
-        let s = v.trim().to_string();
-        if !s.is_empty() {
-            args.push("--pack".into());
-            args.push(s);
-        }
-    }
-    if let Some(v) = level {
-        let s = v.trim().to_string();
-        if !s.is_empty() {
-            args.push("--level".into());
-            args.push(s);
-        }
+    before: Option<String>,
+) -> Result<logs::LogsPage, UiError> {
+    let limit = limit.unwrap_or(200);
+    if limit == 0 {
+        return Err(UiError::invalid("Invalid limit: 0"));
     }
-    if let Some(v) = q {
-        let s = v.trim().to_string();
-        if !s.is_empty() {
-            args.push("--q".into());
-            args.push(s);
-        }
+    let before = before.as_deref().map(logs::Cursor::parse).transpose()?;
+    let filter = logs::LogFilter::new(source, pack, level, q);
+    Ok(logs::page(logs::read_all()?, &filter, limit as usize, before))
+}
+
+/// Starts (or with `enabled: false` stops) pushing new log lines that match
+/// the filter as `kitowall://logs-appended` events.
+#[tauri::command]
+fn kitowall_logs_follow(
+    follower: State<'_, logs::LogFollower>,
+    source: Option<String>,
+    pack: Option<String>,
+    level: Option<String>,
+    q: Option<String>,
+    enabled: Option<bool>,
+) -> Result<Json, UiError> {
+    let enabled = enabled.unwrap_or(true);
+    if enabled {
+        follower.follow(logs::LogFilter::new(source, pack, level, q));
+    } else {
+        follower.stop();
     }
-    let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    Ok(serde_json::json!({"ok": true, "following": enabled}))
 }
 
 #[tauri::command]
-fn kitowall_logs_clear() -> Result<Json, String> {
-    run_kitowall(&["logs", "clear"]).map_err(|e| e.to_string())
+async fn kitowall_logs_clear(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["logs", "clear"]).await
 }
 
 #[tauri::command]
-fn kitowall_install_timer(every: String) -> Result<Json, String> {
+async fn kitowall_install_timer(bridge: State<'_, CliBridge>, every: String) -> Result<Json, UiError> {
     let every_clean = every.trim();
     if every_clean.is_empty() {
-        return Err("every is required".to_string());
+        return Err(UiError::invalid("every is required"));
     }
-    let out = run_kitowall_raw(&["install-systemd", "--every", every_clean]).map_err(|e| e.to_string())?;
+    let out = run_kitowall_raw(&bridge, &["install-systemd", "--every", every_clean]).await?;
     Ok(serde_json::json!({
       "ok": true,
       "every": every_clean,
@@ -879,28 +1284,28 @@ fn kitowall_install_timer(every: String) -> Result<Json, String> {
 }
 
 #[tauri::command]
-fn kitowall_source_keys_get() -> Result<Json, String> {
-    let packs = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
+fn kitowall_source_keys_get() -> Result<Json, UiError> {
+    let config = config::load()?;
     let mut wallhaven = serde_json::json!({"value": null, "apiKeyEnv": null, "pack": null});
     let mut unsplash = serde_json::json!({"value": null, "apiKeyEnv": null, "pack": null});
 
-    if let Some(obj) = packs.get("packs").and_then(|v| v.as_object()) {
-        for (name, pack) in obj {
-            let t = pack.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            if t == "wallhaven" && wallhaven.get("pack").and_then(|v| v.as_str()).is_none() {
-                wallhaven = serde_json::json!({
-                  "value": pack.get("apiKey").and_then(|v| v.as_str()),
-                  "apiKeyEnv": pack.get("apiKeyEnv").and_then(|v| v.as_str()),
-                  "pack": name
-                });
-            }
-            if t == "unsplash" && unsplash.get("pack").and_then(|v| v.as_str()).is_none() {
-                unsplash = serde_json::json!({
-                  "value": pack.get("apiKey").and_then(|v| v.as_str()),
-                  "apiKeyEnv": pack.get("apiKeyEnv").and_then(|v| v.as_str()),
-                  "pack": name
-                });
-            }
+    for (name, pack) in &config.packs {
+        let Some((api_key, api_key_env)) = pack.api_key() else {
+            continue;
+        };
+        if let Some(key) = api_key {
+            error::register_named_secret(&pack_secret_name(name), key);
+        }
+        let slot = match pack {
+            PackConfig::Wallhaven(_) => &mut wallhaven,
+            _ => &mut unsplash,
+        };
+        if slot.get("pack").and_then(|v| v.as_str()).is_none() {
+            *slot = serde_json::json!({
+              "value": api_key,
+              "apiKeyEnv": api_key_env,
+              "pack": name
+            });
         }
     }
 
@@ -912,13 +1317,15 @@ fn kitowall_source_keys_get() -> Result<Json, String> {
 }
 
 #[tauri::command]
-fn kitowall_source_keys_set(
+async fn kitowall_source_keys_set(
+    bridge: State<'_, CliBridge>,
     wallhaven_key: Option<String>,
     unsplash_key: Option<String>,
     wallhaven_env: Option<String>,
     unsplash_env: Option<String>,
     use_env: Option<bool>
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let wallhaven_key = wallhaven_key.unwrap_or_default().trim().to_string();
     let unsplash_key = unsplash_key.unwrap_or_default().trim().to_string();
     let wallhaven_env = wallhaven_env.unwrap_or_else(|| "WALLHAVEN_KEY".to_string()).trim().to_string();
@@ -926,32 +1333,32 @@ fn kitowall_source_keys_set(
     let use_env = use_env.unwrap_or(false);
 
     if !use_env && wallhaven_key.is_empty() && unsplash_key.is_empty() {
-        return Err("No API key provided".to_string());
+        return Err(UiError::invalid("No API key provided"));
     }
 
-    let packs = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
+    let packs = pack_list_uncached(&bridge).await?;
     let mut wallhaven_updated: Vec<String> = vec![];
     let mut unsplash_updated: Vec<String> = vec![];
 
-    if let Some(obj) = packs.get("packs").and_then(|v| v.as_object()) {
-        for (name, pack) in obj {
-            let t = pack.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            if t == "wallhaven" {
-                if use_env {
-                    run_kitowall(&["pack", "set-key", name, "--api-key-env", &wallhaven_env]).map_err(|e| e.to_string())?;
-                } else if !wallhaven_key.is_empty() {
-                    run_kitowall(&["pack", "set-key", name, "--api-key", &wallhaven_key]).map_err(|e| e.to_string())?;
-                }
-                wallhaven_updated.push(name.to_string());
+    for (name, pack) in &packs.packs {
+        let t = pack.pack_type.as_str();
+        if t == "wallhaven" {
+            if use_env {
+                run_kitowall(&bridge, &["pack", "set-key", name, "--api-key-env", &wallhaven_env]).await?;
+                error::forget_named_secret(&pack_secret_name(name));
+            } else if !wallhaven_key.is_empty() {
+                set_pack_key(&bridge, name, &wallhaven_key).await?;
             }
-            if t == "unsplash" {
-                if use_env {
-                    run_kitowall(&["pack", "set-key", name, "--api-key-env", &unsplash_env]).map_err(|e| e.to_string())?;
-                } else if !unsplash_key.is_empty() {
-                    run_kitowall(&["pack", "set-key", name, "--api-key", &unsplash_key]).map_err(|e| e.to_string())?;
-                }
-                unsplash_updated.push(name.to_string());
+            wallhaven_updated.push(name.to_string());
+        }
+        if t == "unsplash" {
+            if use_env {
+                run_kitowall(&bridge, &["pack", "set-key", name, "--api-key-env", &unsplash_env]).await?;
+                error::forget_named_secret(&pack_secret_name(name));
+            } else if !unsplash_key.is_empty() {
+                set_pack_key(&bridge, name, &unsplash_key).await?;
             }
+            unsplash_updated.push(name.to_string());
         }
     }
 
@@ -965,8 +1372,9 @@ fn kitowall_source_keys_set(
 }
 
 #[tauri::command]
-fn kitowall_timer_status() -> Result<Json, String> {
+fn kitowall_timer_status(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
     let timer = systemctl_show(
+        &bridge.audit,
         "kitowall-next.timer",
         &[
             "Id",
@@ -978,6 +1386,7 @@ fn kitowall_timer_status() -> Result<Json, String> {
         ],
     )?;
     let service = systemctl_show(
+        &bridge.audit,
         "kitowall-next.service",
         &["Id", "UnitFileState", "ActiveState", "SubState"],
     )?;
@@ -990,17 +1399,21 @@ fn kitowall_timer_status() -> Result<Json, String> {
 }
 
 #[tauri::command]
-fn kitowall_pack_list_raw() -> Result<Json, String> {
-    run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())
+async fn kitowall_pack_list_raw(bridge: State<'_, CliBridge>) -> Result<PackListPayload, UiError> {
+    pack_list(&bridge).await
 }
 
 #[tauri::command]
-fn kitowall_pack_remove(name: String) -> Result<Json, String> {
-    run_kitowall(&["pack", "remove", &name]).map_err(|e| e.to_string())
+async fn kitowall_pack_remove(bridge: State<'_, CliBridge>, name: String) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
+    let out = run_kitowall(&bridge, &["pack", "remove", &name]).await?;
+    error::forget_named_secret(&pack_secret_name(&name));
+    Ok(out)
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_wallhaven(
+async fn kitowall_pack_upsert_wallhaven(
+    bridge: State<'_, CliBridge>,
     name: String,
     keyword: String,
     subthemes: Option<String>,
@@ -1019,20 +1432,20 @@ fn kitowall_pack_upsert_wallhaven(
     category_anime: Option<bool>,
     category_people: Option<bool>,
     ttl_sec: Option<u32>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     let key = keyword.trim().to_string();
     if pack_name.is_empty() || key.is_empty() {
-        return Err("name and keyword are required".to_string());
+        return Err(UiError::invalid("name and keyword are required"));
     }
 
     // Decide add/update based on current config.
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let mut args: Vec<String> = vec![
@@ -1128,12 +1541,12 @@ fn kitowall_pack_upsert_wallhaven(
     }
 
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     if let Some(api) = api_key {
         let api_clean = api.trim();
         if !api_clean.is_empty() {
-            run_kitowall(&["pack", "set-key", &pack_name, "--api-key", api_clean]).map_err(|e| e.to_string())?;
+            set_pack_key(&bridge, &pack_name, api_clean).await?;
         }
     }
 
@@ -1141,7 +1554,8 @@ fn kitowall_pack_upsert_wallhaven(
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_unsplash(
+async fn kitowall_pack_upsert_unsplash(
+    bridge: State<'_, CliBridge>,
     name: String,
     query: String,
     subthemes: Option<String>,
@@ -1156,19 +1570,19 @@ fn kitowall_pack_upsert_unsplash(
     image_fit: Option<String>,
     image_quality: Option<u32>,
     ttl_sec: Option<u32>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     let q = query.trim().to_string();
     if pack_name.is_empty() || q.is_empty() {
-        return Err("name and query are required".to_string());
+        return Err(UiError::invalid("name and query are required"));
     }
 
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let mut args: Vec<String> = vec![
@@ -1248,12 +1662,12 @@ fn kitowall_pack_upsert_unsplash(
     }
 
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     if let Some(api) = api_key {
         let api_clean = api.trim();
         if !api_clean.is_empty() {
-            run_kitowall(&["pack", "set-key", &pack_name, "--api-key", api_clean]).map_err(|e| e.to_string())?;
+            set_pack_key(&bridge, &pack_name, api_clean).await?;
         }
     }
 
@@ -1261,7 +1675,8 @@ fn kitowall_pack_upsert_unsplash(
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_reddit(
+async fn kitowall_pack_upsert_reddit(
+    bridge: State<'_, CliBridge>,
     name: String,
     subreddits: Option<String>,
     subthemes: Option<String>,
@@ -1271,23 +1686,23 @@ fn kitowall_pack_upsert_reddit(
     ratio_w: Option<u32>,
     ratio_h: Option<u32>,
     ttl_sec: Option<u32>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     if pack_name.is_empty() {
-        return Err("name is required".to_string());
+        return Err(UiError::invalid("name is required"));
     }
 
     let subs = subreddits.unwrap_or_default().trim().to_string();
     if subs.is_empty() {
-        return Err("subreddits is required".to_string());
+        return Err(UiError::invalid("subreddits is required"));
     }
 
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let mut args: Vec<String> = vec![
@@ -1333,13 +1748,14 @@ fn kitowall_pack_upsert_reddit(
     }
 
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     Ok(serde_json::json!({"ok": true, "name": pack_name, "action": action, "type": "reddit"}))
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_generic_json(
+async fn kitowall_pack_upsert_generic_json(
+    bridge: State<'_, CliBridge>,
     name: String,
     endpoint: String,
     image_path: String,
@@ -1351,20 +1767,20 @@ fn kitowall_pack_upsert_generic_json(
     author_url_prefix: Option<String>,
     domain: Option<String>,
     ttl_sec: Option<u32>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     let endpoint_clean = endpoint.trim().to_string();
     let image_path_clean = image_path.trim().to_string();
     if pack_name.is_empty() || endpoint_clean.is_empty() || image_path_clean.is_empty() {
-        return Err("name, endpoint and imagePath are required".to_string());
+        return Err(UiError::invalid("name, endpoint and imagePath are required"));
     }
 
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let mut args: Vec<String> = vec![
@@ -1434,13 +1850,14 @@ fn kitowall_pack_upsert_generic_json(
     }
 
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     Ok(serde_json::json!({"ok": true, "name": pack_name, "action": action, "type": "generic_json"}))
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_static_url(
+async fn kitowall_pack_upsert_static_url(
+    bridge: State<'_, CliBridge>,
     name: String,
     url: Option<String>,
     urls: Option<String>,
@@ -1451,24 +1868,24 @@ fn kitowall_pack_upsert_static_url(
     different_images: Option<bool>,
     count: Option<u32>,
     ttl_sec: Option<u32>,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     if pack_name.is_empty() {
-        return Err("name is required".to_string());
+        return Err(UiError::invalid("name is required"));
     }
 
     let url_clean = url.unwrap_or_default().trim().to_string();
     let urls_clean = urls.unwrap_or_default().trim().to_string();
     if url_clean.is_empty() && urls_clean.is_empty() {
-        return Err("url or urls is required".to_string());
+        return Err(UiError::invalid("url or urls is required"));
     }
 
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let mut args: Vec<String> = vec![
@@ -1529,31 +1946,32 @@ fn kitowall_pack_upsert_static_url(
     }
 
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     Ok(serde_json::json!({"ok": true, "name": pack_name, "action": action, "type": "static_url"}))
 }
 
 #[tauri::command]
-fn kitowall_pack_upsert_local(
+async fn kitowall_pack_upsert_local(
+    bridge: State<'_, CliBridge>,
     name: String,
     paths: String,
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
+    let _config = bridge.lock_config().await;
     let pack_name = name.trim().to_string();
     if pack_name.is_empty() {
-        return Err("name is required".to_string());
+        return Err(UiError::invalid("name is required"));
     }
     let paths_clean = paths.trim().to_string();
     if paths_clean.is_empty() {
-        return Err("paths is required".to_string());
+        return Err(UiError::invalid("paths is required"));
     }
 
-    let raw = run_kitowall(&["pack", "list"]).map_err(|e| e.to_string())?;
-    let exists = raw
-        .get("packs")
-        .and_then(|v| v.as_object())
-        .map(|o| o.contains_key(&pack_name))
-        .unwrap_or(false);
+    let exists = pack_list_uncached(&bridge)
+        .await
+        ?
+        .packs
+        .contains_key(&pack_name);
     let action = if exists { "update" } else { "add" };
 
     let args: Vec<String> = vec![
@@ -1566,21 +1984,21 @@ fn kitowall_pack_upsert_local(
         paths_clean,
     ];
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())?;
+    run_kitowall(&bridge, &refs).await?;
 
     Ok(serde_json::json!({"ok": true, "name": pack_name, "action": action, "type": "local"}))
 }
 
 #[tauri::command]
-fn kitowall_pick_folder() -> Result<Json, String> {
+fn kitowall_pick_folder() -> Result<Json, UiError> {
     let selected = rfd::FileDialog::new().pick_folder();
     let path = selected.map(|p| p.to_string_lossy().to_string());
     Ok(serde_json::json!({ "path": path }))
 }
 
 #[tauri::command]
-fn kitowall_kitsune_status() -> Result<Json, String> {
-    let mut cmd_parts = resolve_kitsune_cmd();
+async fn kitowall_kitsune_status(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    let mut cmd_parts = bridge.tools.current().await.kitsune;
     let base = cmd_parts.remove(0);
     if base == "__missing_kitsune_cli__" {
         return Ok(serde_json::json!({
@@ -1592,17 +2010,18 @@ fn kitowall_kitsune_status() -> Result<Json, String> {
         }));
     }
 
-    let mut command = host_aware_command(&base);
+    let mut command = host_env_command(&base);
     if !cmd_parts.is_empty() {
         command.args(cmd_parts);
     }
     command.arg("help");
-    let output = command.output().map_err(|e| e.to_string())?;
+    let output = bridge
+        .exec(command, CallOptions::default())
+        .await
+        .map_err(UiError::from)?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let message = if !stderr.is_empty() { stderr } else { stdout };
+        let message = failure_message(&output);
         return Ok(serde_json::json!({
             "ok": true,
             "installed": false,
@@ -1612,7 +2031,7 @@ fn kitowall_kitsune_status() -> Result<Json, String> {
         }));
     }
 
-    let help_text = String::from_utf8_lossy(&output.stdout).to_string();
+    let help_text = output.stdout;
     let mut commands: Vec<String> = vec![];
     let mut sections: Vec<String> = vec![];
     let mut command_seen: HashSet<String> = HashSet::new();
@@ -1656,87 +2075,95 @@ fn kitowall_kitsune_status() -> Result<Json, String> {
 }
 
 #[tauri::command]
-fn kitowall_kitsune_run(args: Vec<String>) -> Result<Json, String> {
+async fn kitowall_kitsune_run(
+    bridge: State<'_, CliBridge>,
+    args: Vec<String>,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Json, UiError> {
     if args.is_empty() {
-        return Err("kitsune args are required".to_string());
+        return Err(UiError::invalid("kitsune args are required"));
     }
 
-    let mut cmd_parts = resolve_kitsune_cmd();
+    let mut cmd_parts = bridge.tools.current().await.kitsune;
     let base = cmd_parts.remove(0);
     if base == "__missing_kitsune_cli__" {
-        return Err("kitsune CLI not found on host".to_string());
+        return Err(UiError::CliMissing("kitsune CLI".to_string()));
     }
 
-    let mut command = host_aware_command(&base);
+    let mut command = host_env_command(&base);
     if !cmd_parts.is_empty() {
         command.args(cmd_parts);
     }
     command.args(&args);
-    let output = command.output().map_err(|e| e.to_string())?;
+    let output = bridge
+        .exec(command, CallOptions::from_ui(request_id, timeout_ms))
+        .await
+        .map_err(UiError::from)?;
 
     let exit_code = output.status.code().unwrap_or(-1);
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
     Ok(serde_json::json!({
         "ok": output.status.success(),
         "exitCode": exit_code,
-        "stdout": stdout,
-        "stderr": stderr,
+        "stdout": output.stdout,
+        "stderr": output.stderr,
         "args": args
     }))
 }
 
 #[tauri::command]
-async fn kitowall_live_run(args: Vec<String>) -> Result<Json, String> {
+async fn kitowall_live_run(
+    bridge: State<'_, CliBridge>,
+    args: Vec<String>,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Json, UiError> {
     if args.is_empty() {
-        return Err("live args are required".to_string());
+        return Err(UiError::invalid("live args are required"));
     }
-    tauri::async_runtime::spawn_blocking(move || {
-        let mut full: Vec<String> = vec!["live".into()];
-        full.extend(args);
-        let refs: Vec<&str> = full.iter().map(String::as_str).collect();
-        run_kitowall(&refs).map_err(|e| e.to_string())
-    })
-    .await
-    .map_err(|e| e.to_string())?
+    let mut full: Vec<String> = vec!["live".into()];
+    full.extend(args);
+    let opts = CallOptions::from_ui(request_id, timeout_ms).long_running();
+    run_kitowall_opts(&bridge, full, opts).await
 }
 
 #[tauri::command]
-fn kitowall_open_path(path: String) -> Result<Json, String> {
+fn kitowall_open_path(path: String) -> Result<Json, UiError> {
     let p = PathBuf::from(path.trim());
-    if !p.exists() {
-        return Err(format!("Path not found: {}", p.display()));
+    if !host::path_exists(&p) {
+        return Err(UiError::invalid(format!("Path not found: {}", p.display())));
     }
     let status = host_aware_command("xdg-open")
         .arg(&p)
         .status()
-        .map_err(|e| format!("failed to run xdg-open: {}", e))?;
+        .map_err(|e| UiError::Host(format!("failed to run xdg-open: {}", e)))?;
     if !status.success() {
-        return Err(format!("xdg-open failed with status: {}", status));
+        return Err(UiError::Host(format!("xdg-open failed with status: {}", status)));
     }
     Ok(serde_json::json!({"ok": true, "path": p}))
 }
 
 #[tauri::command]
-fn kitowall_open_url(url: String) -> Result<Json, String> {
+fn kitowall_open_url(url: String) -> Result<Json, UiError> {
     let u = url.trim().to_string();
     let lower = u.to_ascii_lowercase();
     if !(lower.starts_with("http://") || lower.starts_with("https://")) {
-        return Err(format!("Unsupported url: {}", u));
+        return Err(UiError::invalid(format!("Unsupported url: {}", u)));
     }
     let status = host_aware_command("xdg-open")
         .arg(&u)
         .status()
-        .map_err(|e| format!("failed to run xdg-open: {}", e))?;
+        .map_err(|e| UiError::Host(format!("failed to run xdg-open: {}", e)))?;
     if !status.success() {
-        return Err(format!("xdg-open failed with status: {}", status));
+        return Err(UiError::Host(format!("xdg-open failed with status: {}", status)));
     }
     Ok(serde_json::json!({"ok": true, "url": u}))
 }
 
 #[tauri::command]
-fn kitowall_we_search(
+async fn kitowall_we_search(
+    bridge: State<'_, CliBridge>,
     text: Option<String>,
     tags: Option<String>,
     sort: Option<String>,
@@ -1744,7 +2171,7 @@ fn kitowall_we_search(
     page_size: Option<u32>,
     days: Option<u32>,
     fixtures: Option<bool>
-) -> Result<Json, String> {
+) -> Result<Json, UiError> {
     let mut args: Vec<String> = vec!["we".into(), "search".into()];
     if let Some(v) = text {
         let s = v.trim().to_string();
@@ -1783,33 +2210,37 @@ fn kitowall_we_search(
         args.push("--fixtures".into());
     }
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &refs).await
 }
 
 #[tauri::command]
-fn kitowall_we_details(publishedfileid: String, fixtures: Option<bool>) -> Result<Json, String> {
+async fn kitowall_we_details(bridge: State<'_, CliBridge>, publishedfileid: String, fixtures: Option<bool>) -> Result<Json, UiError> {
     if publishedfileid.trim().is_empty() {
-        return Err("publishedfileid is required".to_string());
+        return Err(UiError::invalid("publishedfileid is required"));
     }
     let mut args: Vec<String> = vec!["we".into(), "details".into(), publishedfileid.trim().to_string()];
     if fixtures.unwrap_or(false) {
         args.push("--fixtures".into());
     }
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &refs).await
 }
 
 #[tauri::command]
-fn kitowall_we_download(
+async fn kitowall_we_download<R: Runtime>(
+    app: AppHandle<R>,
+    bridge: State<'_, CliBridge>,
     publishedfileid: String,
     target_dir: Option<String>,
     steam_user: Option<String>,
     steam_password_env: Option<String>,
     steam_guard: Option<String>,
-    coexist: Option<bool>
-) -> Result<Json, String> {
+    coexist: Option<bool>,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Json, UiError> {
     if publishedfileid.trim().is_empty() {
-        return Err("publishedfileid is required".to_string());
+        return Err(UiError::invalid("publishedfileid is required"));
     }
     let mut args: Vec<String> = vec!["we".into(), "download".into(), publishedfileid.trim().to_string()];
     if let Some(v) = target_dir {
@@ -1833,163 +2264,151 @@ fn kitowall_we_download(
             args.push(s);
         }
     }
-    if let Some(v) = steam_guard {
-        let s = v.trim().to_string();
-        if !s.is_empty() {
-            args.push("--steam-guard".into());
-            args.push(s);
-        }
-    }
     if coexist.unwrap_or(false) {
         args.push("--coexist".into());
     }
-    let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    let mut opts = CallOptions::from_ui(request_id, timeout_ms).long_running();
+    if let Some(code) = steam_guard.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+        error::register_secret(&code);
+        args.push("--secrets-stdin".into());
+        opts = opts.with_stdin(serde_json::json!({"steamGuard": code}).to_string());
+    }
+    run_kitowall_streaming(&app, &bridge, args, opts).await
 }
 
 #[tauri::command]
-fn kitowall_we_job(job_id: String) -> Result<Json, String> {
+async fn kitowall_we_job(bridge: State<'_, CliBridge>, job_id: String) -> Result<WorkshopJob, UiError> {
     if job_id.trim().is_empty() {
-        return Err("job_id is required".to_string());
+        return Err(UiError::invalid("job_id is required"));
     }
-    run_kitowall(&["we", "job", job_id.trim()]).map_err(|e| e.to_string())
+    query_kitowall(&bridge, &["we", "job", job_id.trim()]).await
 }
 
 #[tauri::command]
-fn kitowall_we_jobs(limit: Option<u32>) -> Result<Json, String> {
+async fn kitowall_we_jobs(bridge: State<'_, CliBridge>, limit: Option<u32>) -> Result<WorkshopJobsPayload, UiError> {
     let mut args: Vec<String> = vec!["we".into(), "jobs".into()];
     if let Some(v) = limit {
         args.push("--limit".into());
         args.push(v.to_string());
     }
     let refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_kitowall(&refs).map_err(|e| e.to_string())
+    query_kitowall(&bridge, &refs).await
 }
 
 #[tauri::command]
-fn kitowall_we_library() -> Result<Json, String> {
-    run_kitowall(&["we", "library"]).map_err(|e| e.to_string())
+async fn kitowall_we_library(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "library"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_coexist_enter() -> Result<Json, String> {
-    run_kitowall(&["we", "coexist", "enter"]).map_err(|e| e.to_string())
+async fn kitowall_we_coexist_enter(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "coexist", "enter"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_coexist_exit() -> Result<Json, String> {
-    run_kitowall(&["we", "coexist", "exit"]).map_err(|e| e.to_string())
+async fn kitowall_we_coexist_exit(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "coexist", "exit"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_coexist_status() -> Result<Json, String> {
-    run_kitowall(&["we", "coexist", "status"]).map_err(|e| e.to_string())
+async fn kitowall_we_coexist_status(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "coexist", "status"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_set_api_key(api_key: String) -> Result<Json, String> {
+async fn kitowall_we_set_api_key(bridge: State<'_, CliBridge>, api_key: String) -> Result<Json, UiError> {
     let key = api_key.trim().to_string();
     if key.is_empty() {
-        return Err("api_key is required".to_string());
+        return Err(UiError::invalid("api_key is required"));
     }
-    run_kitowall(&["we", "config", "set-api-key", &key]).map_err(|e| e.to_string())
+    let out =
+        run_kitowall_with_secrets(&bridge, &["we", "config", "set-api-key"], serde_json::json!({"apiKey": key})).await?;
+    error::register_named_secret("we-api-key", &key);
+    Ok(out)
 }
 
 #[tauri::command]
-fn kitowall_we_get_steam_roots() -> Result<Json, String> {
-    run_kitowall(&["we", "config", "get-steam-roots"]).map_err(|e| e.to_string())
+async fn kitowall_we_get_steam_roots(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "config", "get-steam-roots"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_set_steam_roots(roots_csv: String) -> Result<Json, String> {
+async fn kitowall_we_set_steam_roots(bridge: State<'_, CliBridge>, roots_csv: String) -> Result<Json, UiError> {
     let roots = roots_csv.trim().to_string();
-    run_kitowall(&["we", "config", "set-steam-roots", &roots]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["we", "config", "set-steam-roots", &roots]).await
 }
 
 #[tauri::command]
-fn kitowall_we_scan_steam() -> Result<Json, String> {
-    run_kitowall(&["we", "scan-steam"]).map_err(|e| e.to_string())
+async fn kitowall_we_scan_steam(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "scan-steam"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_sync_steam() -> Result<Json, String> {
-    run_kitowall(&["we", "sync-steam"]).map_err(|e| e.to_string())
+async fn kitowall_we_sync_steam(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "sync-steam"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_app_status() -> Result<Json, String> {
-    run_kitowall(&["we", "app-status"]).map_err(|e| e.to_string())
+async fn kitowall_we_app_status(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "app-status"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_active() -> Result<Json, String> {
-    run_kitowall(&["we", "active"]).map_err(|e| e.to_string())
+async fn kitowall_we_active(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "active"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_stop_all() -> Result<Json, String> {
-    run_kitowall(&["we", "stop", "--all"]).map_err(|e| e.to_string())
+async fn kitowall_we_stop_all(bridge: State<'_, CliBridge>) -> Result<Json, UiError> {
+    run_kitowall(&bridge, &["we", "stop", "--all"]).await
 }
 
 #[tauri::command]
-fn kitowall_we_apply(id: String, monitor: String, backend: Option<String>) -> Result<Json, String> {
+async fn kitowall_we_apply(bridge: State<'_, CliBridge>, id: String, monitor: String, backend: Option<String>) -> Result<Json, UiError> {
     let id_clean = id.trim().to_string();
     let monitor_clean = monitor.trim().to_string();
     if id_clean.is_empty() || monitor_clean.is_empty() {
-        return Err("id and monitor are required".to_string());
+        return Err(UiError::invalid("id and monitor are required"));
     }
     let backend_clean = backend.unwrap_or_else(|| "auto".to_string());
-    run_kitowall(&["we", "apply", &id_clean, "--monitor", &monitor_clean, "--backend", backend_clean.trim()]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["we", "apply", &id_clean, "--monitor", &monitor_clean, "--backend", backend_clean.trim()]).await
 }
 
 #[tauri::command]
-fn kitowall_we_apply_map(map: String, backend: Option<String>) -> Result<Json, String> {
+async fn kitowall_we_apply_map(bridge: State<'_, CliBridge>, map: String, backend: Option<String>) -> Result<Json, UiError> {
     let map_clean = map.trim().to_string();
     if map_clean.is_empty() {
-        return Err("map is required".to_string());
+        return Err(UiError::invalid("map is required"));
     }
     let backend_clean = backend.unwrap_or_else(|| "auto".to_string());
-    run_kitowall(&["we", "apply", "--map", &map_clean, "--backend", backend_clean.trim()]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["we", "apply", "--map", &map_clean, "--backend", backend_clean.trim()]).await
 }
 
 #[tauri::command]
-fn kitowall_we_stop_monitor(monitor: String) -> Result<Json, String> {
+async fn kitowall_we_stop_monitor(bridge: State<'_, CliBridge>, monitor: String) -> Result<Json, UiError> {
     let monitor_clean = monitor.trim().to_string();
     if monitor_clean.is_empty() {
-        return Err("monitor is required".to_string());
+        return Err(UiError::invalid("monitor is required"));
     }
-    run_kitowall(&["we", "stop", "--monitor", &monitor_clean]).map_err(|e| e.to_string())
+    run_kitowall(&bridge, &["we", "stop", "--monitor", &monitor_clean]).await
 }
 
 #[tauri::command]
-fn kitowall_file_data_url(path: String) -> Result<Json, String> {
+fn kitowall_file_data_url(path: String) -> Result<Json, UiError> {
     let raw = path.trim();
     if raw.is_empty() {
-        return Err("path is required".to_string());
+        return Err(UiError::invalid("path is required"));
     }
     let p = PathBuf::from(raw);
     if !p.exists() {
-        return Err(format!("file not found: {}", p.display()));
+        return Err(UiError::invalid(format!("file not found: {}", p.display())));
     }
-    let meta = fs::metadata(&p).map_err(|e| e.to_string())?;
+    let meta = fs::metadata(&p)?;
     if meta.len() > 8 * 1024 * 1024 {
-        return Err("file too large for preview data url (>8MB)".to_string());
-    }
-    let bytes = fs::read(&p).map_err(|e| e.to_string())?;
-    let ext = p
-        .extension()
-        .and_then(|v| v.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase();
-    let mime = match ext.as_str() {
-        "jpg" | "jpeg" => "image/jpeg",
-        "png" => "image/png",
-        "webp" => "image/webp",
-        "gif" => "image/gif",
-        "bmp" => "image/bmp",
-        "avif" => "image/avif",
-        _ => "application/octet-stream",
-    };
+        return Err(UiError::invalid("file too large for preview data url (>8MB)"));
+    }
+    let bytes = fs::read(&p)?;
+    let mime = media::mime_for(&p);
     let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
     Ok(serde_json::json!({
       "ok": true,
@@ -1998,20 +2417,164 @@ fn kitowall_file_data_url(path: String) -> Result<Json, String> {
     }))
 }
 
+/// Filesystem watchers kept alive for the lifetime of the app.
+#[derive(Default)]
+struct Watchers {
+    state: Mutex<Option<watch::DirWatcher>>,
+    config: Mutex<Option<watch::DirWatcher>>,
+    library: Mutex<Option<(library::Library, watch::DirWatcher)>>,
+    /// Roots of the watched library, for the `kitowall://` scheme. Kept apart
+    /// from `library`, which stays locked while a new watcher scans.
+    library_roots: RwLock<Option<Vec<PathBuf>>>,
+}
+
+/// Watches every library root and emits `kitowall://library-changed` with the
+/// added, modified and removed images. New and changed images are queued for
+/// thumbnails, as is the whole library when the watch starts.
+fn watch_library<R: Runtime>(app: &AppHandle<R>, library: library::Library) -> Result<watch::DirWatcher, UiError> {
+    let dirs: Vec<_> = library.roots.iter().map(|r| (r.path.clone(), notify::RecursiveMode::Recursive)).collect();
+    let mut known = app.state::<index::SharedIndex>().list(&library)?;
+    let thumbnails = app.state::<thumbs::Thumbnails>();
+    thumbnails.retain(&known);
+    thumbnails.attach(&mut known);
+    let live = library.clone();
+    let tracker = Mutex::new(library::LibraryTracker::new(library, known.into_iter().map(|i| PathBuf::from(i.path))));
+    let handle = app.clone();
+    let watcher = watch::watch(&dirs, std::time::Duration::from_millis(500), move |touched| {
+        handle.state::<index::SharedIndex>().mark_dirty(&touched);
+        let mut changes = match tracker.lock() {
+            Ok(mut tracker) => tracker.apply(touched),
+            Err(_) => return,
+        };
+        if !changes.is_empty() {
+            handle.state::<thumbs::Thumbnails>().attach(changes.iter_mut().filter_map(|c| c.item_mut()));
+            let _ = handle.emit(LIBRARY_CHANGED_EVENT, serde_json::json!({"changes": changes, "rescan": false}));
+        }
+    })?;
+    app.state::<index::SharedIndex>().go_live(&live);
+    Ok(watcher)
+}
+
+/// Starts the thumbnail worker, which emits `kitowall://thumbnail-ready` as each
+/// thumbnail lands.
+fn start_thumbnails<R: Runtime>(app: &AppHandle<R>) -> Result<(), UiError> {
+    let handle = app.clone();
+    app.state::<thumbs::Thumbnails>().start(move |source, thumb, color| {
+        let _ = handle.emit(
+            THUMBNAIL_READY_EVENT,
+            serde_json::json!({"path": source, "thumb": thumb, "dominantColor": color}),
+        );
+    })
+}
+
+/// Roots the `kitowall://` scheme serves from, taken from the watched library
+/// when there is one so requests don't re-read config.json.
+fn media_roots<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let watched = app
+        .try_state::<Watchers>()
+        .and_then(|watchers| watchers.library_roots.read().ok()?.clone());
+    let library_roots = watched.unwrap_or_else(|| {
+        library::Library::load().map(|library| library.root_paths()).unwrap_or_default()
+    });
+    media::allowed_roots(&library_roots)
+}
+
+/// (Re)starts the library watcher if the configured roots changed since the
+/// last call. Returns whether it did.
+fn refresh_library_watch<R: Runtime>(app: &AppHandle<R>) -> Result<bool, UiError> {
+    let library = library::Library::load()?;
+    let watchers = app.state::<Watchers>();
+    let Ok(mut slot) = watchers.library.lock() else {
+        return Ok(false);
+    };
+    if slot.as_ref().is_some_and(|(current, _)| *current == library) {
+        return Ok(false);
+    }
+    if let Ok(mut roots) = watchers.library_roots.write() {
+        *roots = Some(library.root_paths());
+    }
+    // Stop the old watcher before the new one lists the roots.
+    *slot = None;
+    app.state::<index::SharedIndex>().stop_live();
+    let watcher = watch_library(app, library.clone())?;
+    *slot = Some((library, watcher));
+    Ok(true)
+}
+
+/// Rebuilds the library watcher when config.json changes (packs added or
+/// removed, download dir moved) or ui.toml's `[library]` scan rules do; the UI
+/// is told to reload the whole list.
+fn watch_config<R: Runtime>(app: &AppHandle<R>) -> Result<watch::DirWatcher, UiError> {
+    let files = [paths::config_file()?, ui_config::config_path()?];
+    let mut dirs = vec![];
+    for file in &files {
+        let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        fs::create_dir_all(&dir)?;
+        if !dirs.iter().any(|(d, _)| *d == dir) {
+            dirs.push((dir, notify::RecursiveMode::NonRecursive));
+        }
+    }
+    let app = app.clone();
+    watch::watch(&dirs, std::time::Duration::from_millis(300), move |touched| {
+        if !files.iter().any(|file| touched.contains(file)) {
+            return;
+        }
+        match refresh_library_watch(&app) {
+            Ok(true) => {
+                let _ = app.emit(LIBRARY_CHANGED_EVENT, serde_json::json!({"changes": [], "rescan": true}));
+            }
+            Ok(false) => {}
+            Err(err) => eprintln!("[kitowall-ui] library watcher: {err}"),
+        }
+    })
+}
+
+/// Emits `kitowall://state-changed` when the CLI rewrites its state files,
+/// e.g. after a timer-driven rotation, with the new state.json when it changed,
+/// and `kitowall://logs-appended` for followed log lines.
+fn watch_state_dir<R: Runtime>(app: &AppHandle<R>) -> Result<watch::DirWatcher, UiError> {
+    let dir = paths::state_dir()?;
+    fs::create_dir_all(&dir)?;
+    let app = app.clone();
+    watch::watch(&[(dir, notify::RecursiveMode::NonRecursive)], std::time::Duration::from_millis(200), move |touched| {
+        if touched.iter().any(|p| p.file_name().is_some_and(|f| f == logs::LOG_FILE)) {
+            let entries = app.state::<logs::LogFollower>().poll();
+            if !entries.is_empty() {
+                let _ = app.emit(LOGS_APPENDED_EVENT, serde_json::json!({"entries": entries}));
+            }
+        }
+        let files: Vec<&str> = [state::STATE_FILE, state::HISTORY_FILE, state::FAVORITES_FILE]
+            .into_iter()
+            .filter(|name| touched.iter().any(|p| p.file_name().is_some_and(|f| f == *name)))
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+        let mut current = None;
+        if files.contains(&state::STATE_FILE) {
+            app.state::<CliBridge>().cache.forget(&["status"]);
+            current = state::load_state().ok();
+        }
+        if files.contains(&state::FAVORITES_FILE) {
+            app.state::<CliBridge>().cache.forget(&["favorites"]);
+        }
+        let _ = app.emit(STATE_CHANGED_EVENT, serde_json::json!({"files": files, "state": current}));
+    })
+}
+
 #[cfg(target_os = "linux")]
-fn ensure_ui_autostart_entry() -> Result<(), String> {
-    let home = host_home_dir()?;
-    let autostart_dir = PathBuf::from(format!("{home}/.config/autostart"));
-    fs::create_dir_all(&autostart_dir).map_err(|e| e.to_string())?;
+fn ensure_ui_autostart_entry() -> Result<(), UiError> {
+    let autostart_dir = paths::autostart_dir()?;
+    fs::create_dir_all(&autostart_dir)?;
 
-    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let exe = std::env::current_exe()?;
     let exe_str = exe.to_string_lossy();
     let desktop_path = autostart_dir.join("kitowall-ui.desktop");
     let desktop = format!(
         "[Desktop Entry]\nType=Application\nName=Kitowall UI\nComment=Start Kitowall tray on login\nExec=env KITOWALL_START_MINIMIZED=1 {exe}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
         exe = exe_str
     );
-    fs::write(desktop_path, desktop).map_err(|e| e.to_string())?;
+    fs::write(desktop_path, desktop)?;
     Ok(())
 }
 
@@ -2031,6 +2594,16 @@ fn main() {
     }
 
     tauri::Builder::default()
+        .manage(CliBridge::new())
+        .manage(logs::LogFollower::default())
+        .manage(index::SharedIndex::default())
+        .manage(thumbs::Thumbnails::default())
+        .register_asynchronous_uri_scheme_protocol(media::SCHEME, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            std::thread::spawn(move || {
+                responder.respond(media::serve(&media_roots(&app), &request));
+            });
+        })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 api.prevent_close();
@@ -2048,38 +2621,46 @@ fn main() {
                     }
                 }
                 "tray_rotate_now" => {
-                    std::thread::spawn(|| {
-                        let _ = run_kitowall_raw_owned(vec![
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let bridge = app.state::<CliBridge>();
+                        let _ = run_kitowall_raw_owned(&bridge, vec![
                             "rotate-now".to_string(),
                             "--force".to_string(),
-                        ]);
+                        ], CallOptions::default()).await;
                     });
                 }
                 "tray_live_start" => {
-                    std::thread::spawn(|| {
-                        let _ = run_kitowall_raw_owned(vec![
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let bridge = app.state::<CliBridge>();
+                        let _ = run_kitowall_raw_owned(&bridge, vec![
                             "live".to_string(),
                             "service-autostart".to_string(),
                             "start".to_string(),
-                        ]);
+                        ], CallOptions::default()).await;
                     });
                 }
                 "tray_live_restart" => {
-                    std::thread::spawn(|| {
-                        let _ = run_kitowall_raw_owned(vec![
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let bridge = app.state::<CliBridge>();
+                        let _ = run_kitowall_raw_owned(&bridge, vec![
                             "live".to_string(),
                             "service-autostart".to_string(),
                             "restart".to_string(),
-                        ]);
+                        ], CallOptions::default()).await;
                     });
                 }
                 "tray_live_stop" => {
-                    std::thread::spawn(|| {
-                        let _ = run_kitowall_raw_owned(vec![
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let bridge = app.state::<CliBridge>();
+                        let _ = run_kitowall_raw_owned(&bridge, vec![
                             "live".to_string(),
                             "service-autostart".to_string(),
                             "stop".to_string(),
-                        ]);
+                        ], CallOptions::default()).await;
                     });
                 }
                 "tray_quit" => app.exit(0),
@@ -2087,6 +2668,46 @@ fn main() {
             }
         })
         .setup(|app| {
+            let notify_handle = app.handle().clone();
+            app.state::<CliBridge>().cache.on_invalidate(move |reason| {
+                let _ = notify_handle.emit(CACHE_INVALIDATED_EVENT, serde_json::json!({"reason": reason}));
+            });
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let bridge = handle.state::<CliBridge>();
+                refresh_cli_tools(&bridge).await;
+            });
+
+            app.manage(Watchers::default());
+            match watch_state_dir(app.handle()) {
+                Ok(watcher) => {
+                    if let Ok(mut slot) = app.state::<Watchers>().state.lock() {
+                        *slot = Some(watcher);
+                    }
+                }
+                Err(err) => eprintln!("[kitowall-ui] state changes will not be pushed: {err}"),
+            }
+            match watch_config(app.handle()) {
+                Ok(watcher) => {
+                    if let Ok(mut slot) = app.state::<Watchers>().config.lock() {
+                        *slot = Some(watcher);
+                    }
+                }
+                Err(err) => eprintln!("[kitowall-ui] config changes will not be picked up: {err}"),
+            }
+            if let Err(err) = start_thumbnails(app.handle()) {
+                eprintln!("[kitowall-ui] thumbnails are disabled: {err}");
+            }
+
+            // The first scan walks every root, keep it off the main thread.
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                if let Err(err) = refresh_library_watch(&handle) {
+                    eprintln!("[kitowall-ui] library changes will not be pushed: {err}");
+                }
+            });
+
             #[cfg(target_os = "linux")]
             {
                 if let Err(err) = ensure_ui_autostart_entry() {
@@ -2143,12 +2764,18 @@ fn main() {
             kitowall_list_packs,
             kitowall_list_pack_folders,
             kitowall_wallpapers_list,
+            kitowall_wallpaper_info,
+            kitowall_wallpaper_apply,
+            kitowall_duplicates_find,
+            kitowall_duplicates_remove,
             kitowall_open_pack_folder,
             kitowall_settings_get,
             kitowall_settings_set,
             kitowall_history_list,
+            kitowall_state_get,
             kitowall_history_clear,
             kitowall_logs,
+            kitowall_logs_follow,
             kitowall_logs_clear,
             kitowall_favorites_list,
             kitowall_favorite_add,
@@ -2168,6 +2795,7 @@ fn main() {
             kitowall_pick_folder,
             kitowall_preflight_status,
             kitowall_preflight_install,
+            kitowall_paths,
             kitowall_kitsune_status,
             kitowall_kitsune_run,
             kitowall_live_run,
@@ -2195,7 +2823,14 @@ fn main() {
             kitowall_we_stop_monitor,
             kitowall_file_data_url,
             kitowall_native_preview_start,
-            kitowall_native_preview_stop
+            kitowall_native_preview_stop,
+            kitowall_cancel,
+            kitowall_cli_info,
+            kitowall_cli_refresh,
+            kitowall_ui_config_get,
+            kitowall_ui_config_set,
+            kitowall_inflight,
+            kitowall_ui_audit
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,177 @@
+// The `kitowall://` URI scheme. It streams library images, thumbnails and live
+// or workshop previews to the webview with their MIME type and HTTP Range
+// support, so videos can seek without whole files going through data URLs.
+// Only files under the wallpaper roots are served.
+//
+// URLs are `kitowall://localhost/<percent-encoded absolute path>`, which is what
+// `convertFileSrc(path, 'kitowall')` builds on the frontend.
+
+use percent_encoding::percent_decode_str;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::http::{header, Method, Request, Response, StatusCode, Uri};
+
+pub const SCHEME: &str = "kitowall";
+/// Most bytes sent for one ranged request; players ask for the rest as they go.
+/// A plain GET for a bigger clip gets this much as a 206 too, so a multi-GB
+/// video is never read into memory whole.
+const MAX_CHUNK: u64 = 4 * 1024 * 1024;
+
+pub fn mime_for(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "avif" => "image/avif",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Directories the scheme may serve from: the library's roots plus the UI's
+/// own media dirs, canonical; missing ones are left out.
+pub fn allowed_roots(library_roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots = library_roots.to_vec();
+    let extra = [
+        crate::thumbs::thumbs_dir(),
+        crate::paths::live_root(),
+        crate::paths::workshop_root(),
+    ];
+    roots.extend(extra.into_iter().flatten().filter_map(|dir| fs::canonicalize(dir).ok()));
+    roots
+}
+
+/// The absolute path a request names.
+fn requested_path(uri: &Uri) -> Option<PathBuf> {
+    let raw = uri.path().strip_prefix('/').unwrap_or(uri.path());
+    let path = PathBuf::from(percent_decode_str(raw).decode_utf8().ok()?.as_ref());
+    path.is_absolute().then_some(path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    Full,
+    /// Inclusive bounds.
+    Partial { start: u64, end: u64 },
+    Unsatisfiable,
+}
+
+/// Reads a `Range` header against a file of `len` bytes. Only a single byte
+/// range is honored; anything else gets the whole file, as RFC 9110 allows.
+pub fn parse_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some((start, end)) = header
+        .and_then(|h| h.trim().strip_prefix("bytes="))
+        .filter(|spec| !spec.contains(','))
+        .and_then(|spec| spec.split_once('-'))
+    else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        // `bytes=-N`: the last N bytes.
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(n) => ByteRange::Partial {
+                start: len.saturating_sub(n),
+                end: len - 1,
+            },
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = match end {
+        "" => u64::MAX,
+        end => match end.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial {
+        start,
+        end: end.min(len - 1),
+    }
+}
+
+/// Answers one request; errors are bare status codes.
+pub fn serve(roots: &[PathBuf], request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    respond(roots, request).unwrap_or_else(|status| {
+        Response::builder()
+            .status(status)
+            .body(Vec::new())
+            .unwrap_or_default()
+    })
+}
+
+fn respond(roots: &[PathBuf], request: &Request<Vec<u8>>) -> Result<Response<Vec<u8>>, StatusCode> {
+    let head = match *request.method() {
+        Method::GET => false,
+        Method::HEAD => true,
+        _ => return Err(StatusCode::METHOD_NOT_ALLOWED),
+    };
+    let path = requested_path(request.uri()).ok_or(StatusCode::BAD_REQUEST)?;
+    // Canonical first, so `..` and symlinks can't step outside the roots.
+    let path = fs::canonicalize(path).map_err(|_| StatusCode::NOT_FOUND)?;
+    if !roots.iter().any(|root| path.starts_with(root)) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let mut file = File::open(&path).map_err(|_| StatusCode::NOT_FOUND)?;
+    let meta = file.metadata().map_err(|_| StatusCode::NOT_FOUND)?;
+    if !meta.is_file() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let len = meta.len();
+    let range = request.headers().get(header::RANGE).and_then(|v| v.to_str().ok());
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, mime_for(&path))
+        .header(header::ACCEPT_RANGES, "bytes");
+    // Images have to arrive whole to decode; anything else is chunked.
+    let chunked = !mime_for(&path).starts_with("image/");
+    let range = match parse_range(range, len) {
+        ByteRange::Full if chunked && len > MAX_CHUNK => ByteRange::Partial { start: 0, end: len - 1 },
+        range => range,
+    };
+    let (response, start, count) = match range {
+        ByteRange::Full => (response.status(StatusCode::OK), 0, len),
+        ByteRange::Partial { start, end } => {
+            let end = end.min(start + MAX_CHUNK - 1);
+            let response = response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"));
+            (response, start, end - start + 1)
+        }
+        ByteRange::Unsatisfiable => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                .body(Vec::new())
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let mut body = Vec::new();
+    if !head {
+        file.seek(SeekFrom::Start(start))
+            .and_then(|_| file.take(count).read_to_end(&mut body))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    response
+        .header(header::CONTENT_LENGTH, count)
+        .body(body)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
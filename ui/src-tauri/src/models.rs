@@ -0,0 +1,207 @@
+// Typed views of the JSON printed by `src/cli.ts`. A renamed or retyped field
+// fails here as a schema mismatch instead of silently reaching the Svelte UI.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::error::UiError;
+
+type Json = Value;
+
+/// Output schema version this UI build understands; keep in sync with
+/// `CLI_OUTPUT_SCHEMA_VERSION` in `src/cli.ts`.
+pub const CLI_SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub mode: String,
+    pub pack: Option<String>,
+    pub outputs: Vec<String>,
+    pub last_set: BTreeMap<String, String>,
+    pub last_updated: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pack_type: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolSource {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_candidates: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSummary {
+    pub enabled: bool,
+    pub sources: Vec<PoolSource>,
+}
+
+/// `list-packs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPacksPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub packs: Vec<PackSummary>,
+    pub pool: PoolSummary,
+}
+
+/// One raw pack entry from `pack list`. Only the fields the backend reads are
+/// typed; the rest of the pack config is carried through untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackEntry {
+    #[serde(rename = "type")]
+    pub pack_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Json>,
+}
+
+/// `pack list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackListPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub packs: BTreeMap<String, PackEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub pack: String,
+    pub output: String,
+    pub path: String,
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// `history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// `favorites` prints a bare array of paths.
+pub type FavoritesPayload = Vec<String>;
+
+/// A line of logs.jsonl (`SystemLogEntry` in `src/core/logs.ts`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub ts: u64,
+    pub level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<String>,
+    #[serde(default)]
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Json>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionSettings {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub fps: f64,
+    pub duration: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos: Option<String>,
+}
+
+/// `settings get`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub mode: String,
+    pub rotation_interval_seconds: u64,
+    pub transition: TransitionSettings,
+    /// Config problems the UI should show, e.g. packs that were skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// `we job <id>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopJob {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub id: String,
+    pub status: String,
+    pub publishedfileid: String,
+    pub target_dir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub logs: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Json>,
+}
+
+/// `we jobs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkshopJobsPayload {
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+    pub jobs: Vec<WorkshopJob>,
+}
+
+/// Validates a CLI response against `T`. `what` names the CLI command in errors.
+pub fn decode<T: DeserializeOwned>(what: &str, json: Json) -> Result<T, UiError> {
+    if let Some(obj) = json.as_object() {
+        // `--json` error envelope: {ok:false, code, error, hint}
+        if obj.get("ok") == Some(&Json::Bool(false)) {
+            if let Some(err) = obj.get("error").and_then(|v| v.as_str()) {
+                let text = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(str::to_string);
+                return Err(match text("code") {
+                    Some(code) => UiError::ActionRequired {
+                        message: err.to_string(),
+                        code: Some(code),
+                        hint: text("hint"),
+                    },
+                    None => UiError::failed(err),
+                });
+            }
+        }
+        if let Some(found) = obj.get("schemaVersion") {
+            if found.as_u64() != Some(CLI_SCHEMA_VERSION) {
+                return Err(UiError::SchemaMismatch(format!(
+                    "`{what}` reports schemaVersion {found}, this UI supports {CLI_SCHEMA_VERSION}. Update kitowall and the UI to matching versions."
+                )));
+            }
+        }
+    }
+    serde_json::from_value(json).map_err(|e| UiError::SchemaMismatch(format!("`{what}`: {e}")))
+}
@@ -0,0 +1,115 @@
+// Where kitowall keeps its files, following the XDG base directory spec the
+// same way `src/core/paths.ts` does, plus the `KITOWALL_CONFIG` override.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::UiError;
+
+fn home() -> Result<PathBuf, UiError> {
+    crate::host_home_dir().map(PathBuf::from)
+}
+
+fn xdg_dir(var: &str, fallback: &[&str]) -> Result<PathBuf, UiError> {
+    // The spec says relative values are invalid and must be ignored.
+    match crate::host::xdg_var(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Ok(dir),
+        _ => Ok(fallback.iter().fold(home()?, |dir, part| dir.join(part))),
+    }
+}
+
+pub fn config_home() -> Result<PathBuf, UiError> {
+    xdg_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+pub fn state_home() -> Result<PathBuf, UiError> {
+    xdg_dir("XDG_STATE_HOME", &[".local", "state"])
+}
+
+pub fn cache_home() -> Result<PathBuf, UiError> {
+    xdg_dir("XDG_CACHE_HOME", &[".cache"])
+}
+
+pub fn config_dir() -> Result<PathBuf, UiError> {
+    Ok(config_home()?.join("kitowall"))
+}
+
+pub fn state_dir() -> Result<PathBuf, UiError> {
+    Ok(state_home()?.join("kitowall"))
+}
+
+/// Files the UI itself generates (index, thumbnails).
+pub fn cache_dir() -> Result<PathBuf, UiError> {
+    Ok(cache_home()?.join("kitowall"))
+}
+
+/// config.json, or the file named by `KITOWALL_CONFIG`. A relative override is
+/// resolved against the working directory, like `path.resolve` in the CLI.
+pub fn config_file() -> Result<PathBuf, UiError> {
+    match std::env::var("KITOWALL_CONFIG").map(|v| v.trim().to_string()) {
+        Ok(path) if !path.is_empty() => config_override(&path, &std::env::current_dir()?),
+        _ => Ok(config_dir()?.join("config.json")),
+    }
+}
+
+/// A `KITOWALL_CONFIG` value as an absolute path.
+pub fn config_override(value: &str, cwd: &Path) -> Result<PathBuf, UiError> {
+    let path = crate::expand_tilde_path(value)?;
+    Ok(if path.is_absolute() { path } else { cwd.join(path) })
+}
+
+pub fn ui_config_file() -> Result<PathBuf, UiError> {
+    Ok(config_dir()?.join("ui.toml"))
+}
+
+pub fn autostart_dir() -> Result<PathBuf, UiError> {
+    Ok(config_home()?.join("autostart"))
+}
+
+/// Live wallpapers and their previews (`getLiveRoot` in `src/core/live.ts`,
+/// which does not follow XDG).
+pub fn live_root() -> Result<PathBuf, UiError> {
+    Ok(home()?.join("Videos").join("LiveWallpapers"))
+}
+
+/// Workshop downloads and previews (`getWePaths` in `src/core/workshop.ts`).
+pub fn workshop_root() -> Result<PathBuf, UiError> {
+    Ok(home()?.join(".local").join("share").join("kitsune").join("we"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedPaths {
+    pub home: PathBuf,
+    pub config_dir: PathBuf,
+    pub config_file: PathBuf,
+    /// True when `KITOWALL_CONFIG` moved config.json.
+    pub config_overridden: bool,
+    pub ui_config_file: PathBuf,
+    pub state_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub autostart_dir: PathBuf,
+    pub local_bin: PathBuf,
+    pub cargo_bin: PathBuf,
+    pub rendercore_env: PathBuf,
+}
+
+pub fn resolve() -> Result<ResolvedPaths, UiError> {
+    let home = home()?;
+    let config_dir = config_dir()?;
+    let config_file = config_file()?;
+    Ok(ResolvedPaths {
+        config_overridden: config_file != config_dir.join("config.json"),
+        config_file,
+        config_dir,
+        ui_config_file: ui_config_file()?,
+        state_dir: state_dir()?,
+        cache_dir: cache_dir()?,
+        autostart_dir: autostart_dir()?,
+        local_bin: home.join(".local").join("bin"),
+        cargo_bin: home.join(".cargo").join("bin"),
+        // kitsune-rendercore hardcodes ~/.config, it does not read XDG_CONFIG_HOME.
+        rendercore_env: home.join(".config").join("kitsune-rendercore").join("env"),
+        home,
+    })
+}
@@ -0,0 +1,129 @@
+// Seam between the Tauri commands and the kitowall process. Commands only build
+// argv and interpret the output; `HostRunner` is the one place that spawns.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::bridge::{CallOptions, CliBridge, CliOutput, LineSink};
+use crate::error::UiError;
+
+pub type RunFuture<'a> = Pin<Box<dyn Future<Output = Result<CliOutput, UiError>> + Send + 'a>>;
+
+pub trait CliRunner: Send + Sync {
+    /// Runs `kitowall <args>` and returns its raw output.
+    fn run<'a>(
+        &'a self,
+        bridge: &'a CliBridge,
+        args: Vec<String>,
+        opts: CallOptions,
+        sink: Option<LineSink<'a>>,
+    ) -> RunFuture<'a>;
+}
+
+/// Spawns the resolved kitowall CLI on the host.
+pub struct HostRunner;
+
+impl CliRunner for HostRunner {
+    fn run<'a>(
+        &'a self,
+        bridge: &'a CliBridge,
+        args: Vec<String>,
+        opts: CallOptions,
+        sink: Option<LineSink<'a>>,
+    ) -> RunFuture<'a> {
+        Box::pin(crate::exec_host_kitowall(bridge, args, opts, sink))
+    }
+}
+
+#[cfg(test)]
+pub mod fake {
+    use super::{CliRunner, RunFuture};
+    use crate::bridge::{CallOptions, CliBridge, CliOutput, LineSink};
+    use serde_json::Value;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone)]
+    pub struct RecordedCall {
+        pub args: Vec<String>,
+        pub stdin: Option<String>,
+    }
+
+    struct Reply {
+        prefix: Vec<String>,
+        code: i32,
+        stdout: String,
+        stderr: String,
+    }
+
+    #[derive(Default)]
+    struct Inner {
+        calls: Mutex<Vec<RecordedCall>>,
+        replies: Mutex<Vec<Reply>>,
+    }
+
+    /// Records every argv and answers with canned output. Replies are matched
+    /// by argv prefix, first match wins; unmatched calls get `{"ok":true}`.
+    #[derive(Clone, Default)]
+    pub struct FakeRunner {
+        inner: Arc<Inner>,
+    }
+
+    impl FakeRunner {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn reply(self, prefix: &[&str], json: Value) -> Self {
+            self.reply_raw(prefix, 0, &json.to_string(), "")
+        }
+
+        pub fn reply_raw(self, prefix: &[&str], code: i32, stdout: &str, stderr: &str) -> Self {
+            self.inner.replies.lock().unwrap().push(Reply {
+                prefix: prefix.iter().map(|s| s.to_string()).collect(),
+                code,
+                stdout: stdout.to_string(),
+                stderr: stderr.to_string(),
+            });
+            self
+        }
+
+        pub fn calls(&self) -> Vec<RecordedCall> {
+            self.inner.calls.lock().unwrap().clone()
+        }
+
+        pub fn argv(&self) -> Vec<Vec<String>> {
+            self.calls().into_iter().map(|c| c.args).collect()
+        }
+    }
+
+    impl CliRunner for FakeRunner {
+        fn run<'a>(
+            &'a self,
+            _bridge: &'a CliBridge,
+            args: Vec<String>,
+            opts: CallOptions,
+            _sink: Option<LineSink<'a>>,
+        ) -> RunFuture<'a> {
+            let replies = self.inner.replies.lock().unwrap();
+            let reply = replies.iter().find(|r| args.starts_with(&r.prefix));
+            let (code, stdout, stderr) = match reply {
+                Some(r) => (r.code, r.stdout.clone(), r.stderr.clone()),
+                None => (0, r#"{"ok":true}"#.to_string(), String::new()),
+            };
+            drop(replies);
+            self.inner.calls.lock().unwrap().push(RecordedCall {
+                args,
+                stdin: opts.stdin,
+            });
+            let output = CliOutput {
+                request_id: opts.request_id.unwrap_or_else(|| "fake".to_string()),
+                status: ExitStatus::from_raw(code << 8),
+                stdout,
+                stderr,
+            };
+            Box::pin(async move { Ok(output) })
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// Native readers for the CLI's runtime files in the state dir (state.json,
+// history.json, favorites.json). Polling these through Node was the slowest
+// part of the Control Center; they are plain JSON the UI can read itself.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::UiError;
+use crate::models::{HistoryEntry, HistoryPayload, CLI_SCHEMA_VERSION};
+
+pub const STATE_FILE: &str = "state.json";
+pub const HISTORY_FILE: &str = "history.json";
+pub const FAVORITES_FILE: &str = "favorites.json";
+
+/// `State` in `src/core/state.ts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeState {
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u64,
+    pub mode: String,
+    pub current_pack: Option<String>,
+    pub last_outputs: Vec<String>,
+    /// Output name to the image currently on it.
+    pub last_set: BTreeMap<String, String>,
+    pub last_updated: u64,
+    pub recent_by_output: BTreeMap<String, Vec<String>>,
+    pub recent_global: Vec<String>,
+}
+
+impl Default for RuntimeState {
+    fn default() -> Self {
+        Self {
+            schema_version: 1,
+            mode: "manual".to_string(),
+            current_pack: None,
+            last_outputs: vec![],
+            last_set: BTreeMap::new(),
+            last_updated: 0,
+            recent_by_output: BTreeMap::new(),
+            recent_global: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct HistoryFile {
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct FavoritesFile {
+    favorites: Vec<String>,
+}
+
+/// Missing files read as their default, like `readJson` in the CLI.
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, UiError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(UiError::Host(format!("{}: {e}", path.display()))),
+    };
+    serde_json::from_str(&raw).map_err(|e| UiError::Host(format!("{}: {e}", path.display())))
+}
+
+pub fn load_state() -> Result<RuntimeState, UiError> {
+    read_json(&crate::paths::state_dir()?.join(STATE_FILE))
+}
+
+pub fn load_favorites() -> Result<Vec<String>, UiError> {
+    read_json::<FavoritesFile>(&crate::paths::state_dir()?.join(FAVORITES_FILE)).map(|f| f.favorites)
+}
+
+/// Same payload as `kitowall history --limit <limit>`.
+pub fn load_history(limit: usize) -> Result<HistoryPayload, UiError> {
+    let dir = crate::paths::state_dir()?;
+    let history: HistoryFile = read_json(&dir.join(HISTORY_FILE))?;
+    Ok(history_payload(history.entries, &load_favorites()?, limit))
+}
+
+/// Newest first, cut to `limit`, with `favorite` filled in.
+pub fn history_payload(mut entries: Vec<HistoryEntry>, favorites: &[String], limit: usize) -> HistoryPayload {
+    let favorites: HashSet<&str> = favorites.iter().map(String::as_str).collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries.truncate(limit);
+    for entry in &mut entries {
+        entry.favorite = favorites.contains(entry.path.as_str());
+    }
+    HistoryPayload {
+        schema_version: Some(CLI_SCHEMA_VERSION),
+        entries,
+    }
+}
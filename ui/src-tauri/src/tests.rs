@@ -0,0 +1,1020 @@
+// Command-level tests: each command runs against `FakeRunner`, so they check the
+// exact argv sent to kitowall and how its output is turned into a response.
+
+use serde_json::json;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::Manager;
+
+use super::*;
+use crate::runner::fake::FakeRunner;
+
+fn app_with(fake: &FakeRunner) -> tauri::App<MockRuntime> {
+    let app = mock_app();
+    app.manage(CliBridge::with_runner(fake.clone()));
+    app
+}
+
+fn argv(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tauri::async_runtime::block_on(fut)
+}
+
+fn sh(script: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.args(["-c", script]);
+    cmd
+}
+
+#[test]
+fn bridge_feeds_stdin_and_times_out_children_that_never_read_it() {
+    use std::time::{Duration, Instant};
+    let bridge = CliBridge::new();
+    let out = block_on(bridge.exec(sh("cat"), CallOptions::default().with_stdin("secret".to_string()))).unwrap();
+    assert_eq!(out.stdout, "secret");
+
+    // More than a pipe buffer, so the write blocks until the child reads.
+    let opts = CallOptions {
+        request_id: Some("stuck".to_string()),
+        timeout: Some(Duration::from_millis(200)),
+        stdin: Some("x".repeat(1 << 20)),
+    };
+    let started = Instant::now();
+    let err = block_on(bridge.exec(sh("sleep 5"), opts)).unwrap_err();
+    assert!(matches!(err, ExecError::TimedOut { ref request_id, .. } if request_id == "stuck"), "{err:?}");
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert!(bridge.inflight().is_empty());
+}
+
+#[test]
+fn bridge_cancel_kills_the_call_with_that_request_id() {
+    let bridge = CliBridge::new();
+    let opts = CallOptions::from_ui(Some("slow".to_string()), None);
+    let (result, cancelled) = block_on(async {
+        tokio::join!(bridge.exec(sh("sleep 5"), opts), async {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            bridge.cancel("slow")
+        })
+    });
+    assert!(cancelled);
+    assert!(matches!(result, Err(ExecError::Cancelled { .. })), "{result:?}");
+    assert!(!bridge.cancel("slow"));
+}
+
+#[test]
+fn version_handshake_accepts_only_the_supported_major() {
+    let status = |raw: &str| Handshake::from_version_output(raw).status;
+    assert_eq!(Handshake::from_version_output("v3.6.0\n").cli_version.as_deref(), Some("3.6.0"));
+    assert_eq!(status("3.6.0"), Compat::Ok);
+    assert_eq!(status("3.5.38"), Compat::Incompatible);
+    assert_eq!(status("3.12.1-beta.2+abc"), Compat::Ok);
+    assert_eq!(status("3.4.9"), Compat::Incompatible);
+    assert_eq!(status("3"), Compat::Incompatible);
+    assert_eq!(status("4.0.0"), Compat::Incompatible);
+    assert_eq!(status("kitowall dev build"), Compat::Warn);
+}
+
+#[test]
+fn tool_overrides_split_like_a_shell() {
+    assert_eq!(
+        ui_config::parse_command_line("kitowall", "node '/opt/my apps/cli.js' --flag=\"a b\"").unwrap(),
+        argv(&["node", "/opt/my apps/cli.js", "--flag=a b"])
+    );
+    assert!(ui_config::parse_command_line("mpv", "   ").is_err());
+    assert!(ui_config::parse_command_line("mpv", "mpv 'unterminated").is_err());
+}
+
+#[test]
+fn tool_override_chains_are_followed_and_loops_refused() {
+    let table = |pairs: &'static [(&'static str, &'static str)]| {
+        move |tool: &str| {
+            pairs
+                .iter()
+                .find(|(name, _)| *name == tool)
+                .map(|(_, cmd)| ui_config::parse_command_line(tool, cmd).unwrap())
+        }
+    };
+    let chained = table(&[("mpv", "swww --flag"), ("swww", "/usr/bin/swww-git")]);
+    assert_eq!(ui_config::overridden_program("mpv", chained).unwrap(), "/usr/bin/swww-git");
+    assert_eq!(ui_config::overridden_program("cava", table(&[("cava", "cava -p x")])).unwrap(), "cava");
+    assert_eq!(ui_config::overridden_program("kitsune", table(&[])).unwrap(), "kitsune");
+
+    let looped = ui_config::overridden_program("mpv", table(&[("mpv", "swww"), ("swww", "mpv")])).unwrap_err();
+    assert!(matches!(&looped, UiError::BadConfig(msg) if msg.contains("mpv -> swww -> mpv")), "{looped:?}");
+}
+
+#[test]
+fn flatpak_spawn_forwards_session_vars_and_lets_explicit_env_win() {
+    let vars = [
+        ("WAYLAND_DISPLAY", "wayland-1"),
+        ("KITOWALL_CMD", "kitowall --debug"),
+        ("HOME", "/sandbox/home"),
+        ("PATH", "/app/bin"),
+        ("SECRET_TOKEN", "x"),
+    ]
+    .map(|(k, v)| (k.to_string(), v.to_string()));
+    let xdg = |name: &str| (name == "XDG_STATE_HOME").then(|| "/home/u/.state".to_string());
+    let cmd = host::flatpak_spawn("swww", &[("PATH", "/usr/bin".to_string())], vars, xdg);
+
+    assert_eq!(cmd.get_program(), "flatpak-spawn");
+    let args: Vec<String> = cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+    assert_eq!(
+        args,
+        argv(&[
+            "--host",
+            "--watch-bus",
+            "--env=WAYLAND_DISPLAY=wayland-1",
+            "--env=KITOWALL_CMD=kitowall --debug",
+            "--env=XDG_STATE_HOME=/home/u/.state",
+            "--env=PATH=/usr/bin",
+            "swww",
+        ])
+    );
+}
+
+#[test]
+fn audit_redacts_secret_flags_subcommands_and_env() {
+    let args = argv(&[
+        "pack", "set-key", "wh", "--api-key", "k-123456", "--token=t-123456", "--api-keyring", "kept",
+        "--env=WALLHAVEN_API_KEY=abcdef", "--env=PATH=/usr/bin", "we", "config", "set-api-key", "steam-key", "--secret",
+    ]);
+    assert_eq!(
+        audit::redact_args(&args),
+        argv(&[
+            "pack", "set-key", "wh", "--api-key", "<redacted>", "--token=<redacted>", "--api-keyring", "kept",
+            "--env=WALLHAVEN_API_KEY=<redacted>", "--env=PATH=/usr/bin", "we", "config", "set-api-key", "<redacted>",
+            "--secret",
+        ])
+    );
+}
+
+#[test]
+fn scrub_masks_query_keys_only_at_parameter_boundaries() {
+    assert_eq!(
+        error::scrub("GET https://x/api?key=abc123&q=cats&api_key=zzz monkey=banana"),
+        "GET https://x/api?key=<redacted>&q=cats&api_key=<redacted> monkey=banana"
+    );
+    assert_eq!(error::scrub("apikey=first"), "apikey=<redacted>");
+}
+
+#[test]
+fn replaced_and_removed_pack_keys_stop_being_masked() {
+    error::register_named_secret("pack:scrub-test", "old-key-111");
+    assert_eq!(error::scrub("bad old-key-111"), "bad <redacted>");
+
+    error::register_named_secret("pack:scrub-test", "new-key-222");
+    assert_eq!(error::scrub("old-key-111 new-key-222"), "old-key-111 <redacted>");
+
+    error::forget_named_secret("pack:scrub-test");
+    assert_eq!(error::scrub("new-key-222"), "new-key-222");
+}
+
+#[test]
+fn action_required_serializes_the_cli_code() {
+    let err = UiError::ActionRequired {
+        message: "login needed".to_string(),
+        code: Some("steam_guard".to_string()),
+        hint: Some("Enter the Steam Guard code.".to_string()),
+    };
+    let value = serde_json::to_value(&err).unwrap();
+    assert_eq!(value["code"], "action_required");
+    assert_eq!(value["cliCode"], "steam_guard");
+    assert_eq!(value["exitCode"], 2);
+    assert_eq!(serde_json::to_value(UiError::invalid("x")).unwrap()["cliCode"], Json::Null);
+}
+
+fn upsert_wallhaven(
+    app: &tauri::App<MockRuntime>,
+    name: &str,
+    keyword: &str,
+    api_key: Option<&str>,
+) -> Result<Json, UiError> {
+    block_on(kitowall_pack_upsert_wallhaven(
+        app.state(),
+        name.to_string(),
+        keyword.to_string(),
+        None,
+        api_key.map(str::to_string),
+        None,
+        Some(" 100 ".to_string()),
+        None,
+        None,
+        None,
+        None,
+        Some(false),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(60),
+    ))
+}
+
+#[test]
+fn upsert_wallhaven_adds_new_pack_and_sends_key_over_stdin() {
+    let fake = FakeRunner::new().reply(&["pack", "list"], json!({"schemaVersion": 1, "packs": {}}));
+    let app = app_with(&fake);
+
+    let out = upsert_wallhaven(&app, " walls ", "cats", Some(" abc123secret ")).unwrap();
+
+    assert_eq!(out["action"], "add");
+    let calls = fake.calls();
+    assert_eq!(
+        fake.argv(),
+        vec![
+            argv(&["pack", "list"]),
+            argv(&[
+                "pack", "add", "walls", "--type", "wallhaven", "--keyword", "cats", "--purity", "100", "--ai-art",
+                "false", "--ttl-sec", "60",
+            ]),
+            argv(&["pack", "set-key", "walls", "--secrets-stdin"]),
+        ]
+    );
+    let stdin: Json = serde_json::from_str(calls[2].stdin.as_deref().unwrap()).unwrap();
+    assert_eq!(stdin, json!({"apiKey": "abc123secret"}));
+    assert!(calls.iter().all(|c| !c.args.iter().any(|a| a.contains("abc123secret"))));
+}
+
+#[test]
+fn upsert_wallhaven_updates_existing_pack() {
+    let fake = FakeRunner::new().reply(
+        &["pack", "list"],
+        json!({"packs": {"walls": {"type": "wallhaven", "keyword": "dogs"}}}),
+    );
+    let app = app_with(&fake);
+
+    let out = upsert_wallhaven(&app, "walls", "cats", None).unwrap();
+
+    assert_eq!(out["action"], "update");
+    let calls = fake.argv();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(&calls[1][..3], &argv(&["pack", "update", "walls"])[..]);
+}
+
+#[test]
+fn upsert_reads_the_pack_list_past_the_query_cache() {
+    let fake = FakeRunner::new().reply(&["pack", "list"], json!({"packs": {}}));
+    let app = app_with(&fake);
+    block_on(kitowall_pack_list_raw(app.state())).unwrap();
+
+    upsert_wallhaven(&app, "walls", "cats", None).unwrap();
+
+    let lists = fake.argv().iter().filter(|a| a[..] == argv(&["pack", "list"])[..]).count();
+    assert_eq!(lists, 2);
+}
+
+#[test]
+fn upsert_wallhaven_validates_before_running_the_cli() {
+    let fake = FakeRunner::new();
+    let app = app_with(&fake);
+
+    let err = upsert_wallhaven(&app, "walls", "  ", None).unwrap_err();
+
+    assert_eq!(err.code(), "invalid_argument");
+    assert!(fake.calls().is_empty());
+}
+
+#[test]
+fn upsert_stops_when_pack_list_fails() {
+    let fake = FakeRunner::new().reply_raw(&["pack", "list"], 1, "", "config.json is corrupt");
+    let app = app_with(&fake);
+
+    let err = block_on(kitowall_pack_upsert_local(app.state(), "mine".into(), "~/Pictures".into())).unwrap_err();
+
+    assert_eq!(err.code(), "command_failed");
+    assert_eq!(err.stderr(), Some("config.json is corrupt"));
+    assert_eq!(fake.argv(), vec![argv(&["pack", "list"])]);
+}
+
+fn log(ts: u64, level: &str, source: &str, message: &str) -> models::LogEntry {
+    serde_json::from_value(json!({"ts": ts, "level": level, "source": source, "action": "fetch", "message": message}))
+        .unwrap()
+}
+
+#[test]
+fn logs_filter_like_the_cli() {
+    let filter = logs::LogFilter::new(Some(" wallhaven ".into()), Some("".into()), Some("bogus".into()), Some("TIME".into()));
+
+    assert_eq!(filter.level, None);
+    assert!(filter.matches(&log(1, "warn", "wallhaven", "request timeout")));
+    assert!(!filter.matches(&log(1, "warn", "reddit", "request timeout")));
+    assert!(!filter.matches(&log(1, "warn", "wallhaven", "ok")));
+}
+
+#[test]
+fn logs_pages_backwards_across_equal_timestamps() {
+    let entries = vec![log(1, "info", "a", "1"), log(2, "info", "a", "2a"), log(2, "info", "a", "2b"), log(3, "info", "a", "3")];
+    let all = logs::LogFilter::default();
+
+    let first = logs::page(entries.clone(), &all, 2, None);
+    let messages = |p: &logs::LogsPage| p.entries.iter().map(|e| e.message.clone().unwrap()).collect::<Vec<_>>();
+    assert_eq!(messages(&first), ["3", "2a"]);
+    let cursor = first.next_cursor.unwrap();
+    assert_eq!(cursor, logs::Cursor { ts: 2, seen: 1 });
+
+    let second = logs::page(entries, &all, 2, Some(logs::Cursor::parse("2:1").unwrap()));
+    assert_eq!(messages(&second), ["2b", "1"]);
+    assert!(second.next_cursor.is_none());
+}
+
+fn log_lines(lines: &[(u64, &str)]) -> String {
+    lines
+        .iter()
+        .map(|(ts, message)| format!("{}\n", serde_json::to_string(&log(*ts, "info", "a", message)).unwrap()))
+        .collect()
+}
+
+fn tail_messages(tail: &mut logs::LogTail, path: &Path) -> Vec<String> {
+    tail.read_new(path).unwrap().into_iter().map(|e| e.message.unwrap()).collect()
+}
+
+#[test]
+fn log_tail_reads_appends_and_waits_for_partial_lines() {
+    let dir = scratch_dir("log-tail");
+    let path = dir.join(logs::LOG_FILE);
+    fs::write(&path, log_lines(&[(1, "m1"), (2, "m2")])).unwrap();
+    let mut tail = logs::LogTail::at_end(&path);
+    assert!(tail_messages(&mut tail, &path).is_empty());
+
+    let line = log_lines(&[(4, "m4")]);
+    let (head, rest) = line.split_at(10);
+    fs::write(&path, log_lines(&[(1, "m1"), (2, "m2"), (3, "m3")]) + head).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["m3"]);
+    fs::write(&path, log_lines(&[(1, "m1"), (2, "m2"), (3, "m3")]) + head + rest).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["m4"]);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn log_tail_resyncs_when_a_trim_rewrite_keeps_or_grows_the_length() {
+    let dir = scratch_dir("log-trim");
+    let path = dir.join(logs::LOG_FILE);
+    fs::write(&path, log_lines(&[(1, "m1"), (2, "m2"), (3, "m3")])).unwrap();
+    let mut tail = logs::LogTail::at_end(&path);
+
+    // Same length: one line dropped from the front, one of equal size appended.
+    fs::write(&path, log_lines(&[(2, "m2"), (3, "m3"), (4, "m4")])).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["m4"]);
+    // Longer: the appended line is bigger than the dropped one.
+    fs::write(&path, log_lines(&[(3, "m3"), (4, "m4"), (5, "m5 is longer")])).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["m5 is longer"]);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn log_tail_starts_over_after_truncation() {
+    let dir = scratch_dir("log-truncate");
+    let path = dir.join(logs::LOG_FILE);
+    fs::write(&path, log_lines(&[(1, "m1"), (2, "m2")])).unwrap();
+    let mut tail = logs::LogTail::at_end(&path);
+
+    fs::write(&path, log_lines(&[(5, "fresh")])).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["fresh"]);
+    fs::remove_file(&path).unwrap();
+    assert!(tail_messages(&mut tail, &path).is_empty());
+    fs::write(&path, log_lines(&[(6, "again")])).unwrap();
+    assert_eq!(tail_messages(&mut tail, &path), ["again"]);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn log_follower_pushes_only_matching_entries_newest_first() {
+    let dir = scratch_dir("log-follow");
+    let path = dir.join(logs::LOG_FILE);
+    fs::write(&path, log_lines(&[(1, "old")])).unwrap();
+    let follower = logs::LogFollower::with_path(path.clone());
+    assert!(follower.poll().is_empty());
+
+    follower.follow(logs::LogFilter::new(None, None, None, Some("keep".into())));
+    fs::write(&path, log_lines(&[(1, "old"), (2, "keep 1"), (3, "drop"), (4, "keep 2")])).unwrap();
+    let messages: Vec<_> = follower.poll().into_iter().map(|e| e.message.unwrap()).collect();
+    assert_eq!(messages, ["keep 2", "keep 1"]);
+
+    follower.stop();
+    fs::write(&path, log_lines(&[(1, "old"), (5, "keep 3")])).unwrap();
+    assert!(follower.poll().is_empty());
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn we_download_moves_steam_guard_to_stdin() {
+    let fake = FakeRunner::new().reply(&["we", "download"], json!({"ok": true, "id": "job-1"}));
+    let app = app_with(&fake);
+
+    let out = block_on(kitowall_we_download(
+        app.handle().clone(),
+        app.state(),
+        " 123456 ".into(),
+        Some("  ".into()),
+        Some("bob".into()),
+        None,
+        Some(" X9Y8Z7 ".into()),
+        Some(true),
+        Some("dl-1".into()),
+        None,
+    ))
+    .unwrap();
+
+    assert_eq!(out["id"], "job-1");
+    let calls = fake.calls();
+    assert_eq!(
+        calls[0].args,
+        argv(&["we", "download", "123456", "--steam-user", "bob", "--coexist", "--secrets-stdin"])
+    );
+    let stdin: Json = serde_json::from_str(calls[0].stdin.as_deref().unwrap()).unwrap();
+    assert_eq!(stdin, json!({"steamGuard": "X9Y8Z7"}));
+}
+
+#[test]
+fn next_builds_namespace_force_and_pack() {
+    let fake = FakeRunner::new();
+    let app = app_with(&fake);
+
+    block_on(kitowall_next(app.state(), None, Some(true), Some("sao".into()))).unwrap();
+
+    assert_eq!(
+        fake.argv(),
+        vec![argv(&["next", "--namespace", "kitowall", "--json", "--force", "--pack", "sao"])]
+    );
+}
+
+#[test]
+fn exit_code_two_with_json_is_a_response() {
+    let fake = FakeRunner::new().reply_raw(&["next"], 2, r#"{"ok":false,"code":"NO_OUTPUTS"}"#, "");
+    let app = app_with(&fake);
+
+    let out = block_on(kitowall_next(app.state(), None, None, None)).unwrap();
+
+    assert_eq!(out["code"], "NO_OUTPUTS");
+}
+
+#[test]
+fn exit_code_two_without_json_is_action_required() {
+    let fake = FakeRunner::new().reply_raw(&["next"], 2, "", "run kitowall init first");
+    let app = app_with(&fake);
+
+    let err = block_on(kitowall_next(app.state(), None, None, None)).unwrap_err();
+
+    assert_eq!(err.code(), "action_required");
+    assert_eq!(err.exit_code(), Some(2));
+}
+
+#[test]
+fn non_json_success_is_invalid_json() {
+    let fake = FakeRunner::new().reply_raw(&["next"], 0, "applied", "");
+    let app = app_with(&fake);
+
+    let err = block_on(kitowall_next(app.state(), None, None, None)).unwrap_err();
+
+    assert_eq!(err.code(), "invalid_json");
+}
+
+#[test]
+fn source_keys_set_with_env_updates_matching_packs() {
+    let fake = FakeRunner::new().reply(
+        &["pack", "list"],
+        json!({"packs": {
+            "a": {"type": "wallhaven"},
+            "b": {"type": "unsplash"},
+            "c": {"type": "local"}
+        }}),
+    );
+    let app = app_with(&fake);
+
+    let out = block_on(kitowall_source_keys_set(app.state(), None, None, None, Some("MY_UNSPLASH".into()), Some(true)))
+        .unwrap();
+
+    assert_eq!(out["updated"], json!({"wallhaven": ["a"], "unsplash": ["b"]}));
+    assert_eq!(
+        fake.argv()[1..],
+        [
+            argv(&["pack", "set-key", "a", "--api-key-env", "WALLHAVEN_KEY"]),
+            argv(&["pack", "set-key", "b", "--api-key-env", "MY_UNSPLASH"]),
+        ]
+    );
+}
+
+#[test]
+fn source_keys_set_requires_a_key_or_env() {
+    let fake = FakeRunner::new();
+    let app = app_with(&fake);
+
+    let err = block_on(kitowall_source_keys_set(app.state(), None, Some(" ".into()), None, None, None)).unwrap_err();
+
+    assert_eq!(err.code(), "invalid_argument");
+    assert!(fake.calls().is_empty());
+}
+
+#[test]
+fn settings_set_passes_only_given_fields() {
+    let fake = FakeRunner::new();
+    let app = app_with(&fake);
+
+    block_on(kitowall_settings_set(
+        app.state(),
+        Some("rotate".into()),
+        Some(600),
+        None,
+        None,
+        Some(0.7),
+        None,
+        None,
+    ))
+    .unwrap();
+
+    assert_eq!(
+        fake.argv(),
+        vec![argv(&[
+            "settings",
+            "set",
+            "--mode",
+            "rotate",
+            "--rotation-interval-sec",
+            "600",
+            "--transition-duration",
+            "0.7",
+        ])]
+    );
+}
+
+#[test]
+fn we_set_api_key_never_puts_the_key_on_argv() {
+    let fake = FakeRunner::new();
+    let app = app_with(&fake);
+
+    block_on(kitowall_we_set_api_key(app.state(), " steam-web-key-1 ".into())).unwrap();
+
+    let calls = fake.calls();
+    assert_eq!(calls[0].args, argv(&["we", "config", "set-api-key", "--secrets-stdin"]));
+    assert_eq!(calls[0].stdin.as_deref(), Some(r#"{"apiKey":"steam-web-key-1"}"#));
+}
+
+#[test]
+fn cached_queries_are_refetched_after_a_mutation() {
+    let fake = FakeRunner::new().reply(
+        &["list-packs"],
+        json!({"schemaVersion": 1, "packs": [], "pool": {"enabled": false, "sources": []}}),
+    );
+    let app = app_with(&fake);
+
+    block_on(kitowall_list_packs(app.state())).unwrap();
+    block_on(kitowall_list_packs(app.state())).unwrap();
+    assert_eq!(fake.argv().len(), 1);
+
+    block_on(kitowall_settings_set(app.state(), Some("rotate".into()), None, None, None, None, None, None)).unwrap();
+    block_on(kitowall_list_packs(app.state())).unwrap();
+    assert_eq!(fake.argv().len(), 3);
+}
+
+#[test]
+fn read_only_classification() {
+    assert!(cache::is_read_only(&argv(&["logs", "--limit", "10"])));
+    assert!(!cache::is_read_only(&argv(&["logs", "clear"])));
+    assert!(!cache::is_read_only(&argv(&["pack", "add", "x"])));
+    assert!(!cache::is_read_only(&argv(&["some-new-command"])));
+    assert!(cache::is_cacheable(&argv(&["pack", "list"])));
+    assert!(!cache::is_cacheable(&argv(&["we", "jobs"])));
+}
+
+#[test]
+fn config_parses_packs_like_the_cli() {
+    let config = config::parse(
+        r#"{
+            "mode": "rotate",
+            "cache": {"downloadDir": "/walls"},
+            "packs": {
+                "mine": {"type": "local", "paths": "/srv/pics"},
+                "subs": {"type": "reddit", "subreddits": "earthporn, wallpapers,"},
+                "bogus": {"type": "nope"},
+                "broken": {"type": "wallhaven", "ratios": 16}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.mode, config::Mode::Rotate);
+    assert_eq!(config.rotation_interval_seconds, 1800);
+    assert_eq!(config.cache.download_dir, "/walls");
+    assert_eq!(config.packs.keys().collect::<Vec<_>>(), ["mine", "subs"]);
+    assert_eq!(config.local_pack_roots().unwrap(), [(PathBuf::from("/srv/pics"), "mine".to_string())]);
+    let PackConfig::Reddit(reddit) = &config.packs["subs"] else {
+        panic!("expected a reddit pack");
+    };
+    assert_eq!(reddit.subreddits, ["earthporn", "wallpapers"]);
+}
+
+#[test]
+fn config_reports_skipped_packs_as_warnings() {
+    let config = config::parse(
+        r#"{"packs": {"ok": {"type": "local", "paths": []}, "bogus": {"type": "nope"}, "broken": {"type": "wallhaven", "ratios": 16}}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.warnings.len(), 2);
+    assert!(config.warnings[0].starts_with("pack \"bogus\""), "{:?}", config.warnings);
+    assert!(config.warnings[1].starts_with("pack \"broken\""), "{:?}", config.warnings);
+    let settings = serde_json::to_value(config.settings(&state::RuntimeState::default())).unwrap();
+    assert_eq!(settings["warnings"].as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn relative_config_overrides_resolve_against_the_working_directory() {
+    let cwd = Path::new("/work/dir");
+    assert_eq!(paths::config_override("alt.json", cwd).unwrap(), PathBuf::from("/work/dir/alt.json"));
+    assert_eq!(paths::config_override("/etc/kw.json", cwd).unwrap(), PathBuf::from("/etc/kw.json"));
+    assert!(paths::config_override("~/kw.json", cwd).unwrap().is_absolute());
+}
+
+#[test]
+fn settings_take_the_mode_from_state_and_the_rest_from_config() {
+    let config = config::parse(r#"{"mode": "rotate", "rotation_interval_seconds": 600}"#).unwrap();
+    let state = state::RuntimeState { mode: "manual".to_string(), ..Default::default() };
+
+    let settings = config.settings(&state);
+
+    assert_eq!(settings.mode, "manual");
+    assert_eq!(settings.rotation_interval_seconds, 600);
+    assert_eq!(settings.transition.kind, "center");
+    assert!(settings.warnings.is_empty());
+}
+
+#[test]
+fn config_errors_name_the_position_and_newer_schemas() {
+    let err = config::parse("{\n  \"mode\": 3\n}").unwrap_err();
+    assert!(err.contains("line 2"), "{err}");
+    assert!(config::parse(r#"{"schemaVersion": 2}"#).unwrap_err().contains("newer"));
+}
+
+#[test]
+fn history_is_newest_first_with_favorites_marked() {
+    let entry = |timestamp, path: &str| models::HistoryEntry {
+        timestamp,
+        pack: "p".into(),
+        output: "DP-1".into(),
+        path: path.into(),
+        favorite: false,
+    };
+    let entries = vec![entry(1, "/a.jpg"), entry(3, "/c.jpg"), entry(2, "/b.jpg")];
+
+    let payload = state::history_payload(entries, &["/b.jpg".to_string()], 2);
+
+    let paths: Vec<_> = payload.entries.iter().map(|e| (e.path.as_str(), e.favorite)).collect();
+    assert_eq!(paths, [("/c.jpg", false), ("/b.jpg", true)]);
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kitowall-ui-test-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::canonicalize(dir).unwrap()
+}
+
+/// `root` as the download root, under the default scan rules.
+fn one_root_library(root: &Path) -> library::Library {
+    library::Library {
+        download_root: root.to_path_buf(),
+        roots: vec![library::LibraryRoot {
+            path: root.to_path_buf(),
+            pack: None,
+            exclude: library::Excludes::default(),
+        }],
+        max_depth: ui_config::DEFAULT_MAX_DEPTH,
+        include_hidden: false,
+    }
+}
+
+#[test]
+fn library_tracker_reports_added_modified_and_removed() {
+    let root = scratch_dir("library");
+    fs::create_dir_all(root.join("anime")).unwrap();
+    fs::write(root.join("anime/a.jpg"), b"a").unwrap();
+    let library = one_root_library(&root);
+    let mut tracker = library::LibraryTracker::new(library, [root.join("anime/a.jpg")]);
+
+    fs::create_dir_all(root.join("cats")).unwrap();
+    fs::write(root.join("cats/b.png"), b"b").unwrap();
+    fs::remove_dir_all(root.join("anime")).unwrap();
+    let changes = tracker.apply(vec![root.join("cats"), root.join("cats/b.png"), root.join("anime"), root.join("notes.txt")]);
+
+    let summary: Vec<Json> = changes.iter().map(|c| serde_json::to_value(c).unwrap()).collect();
+    assert_eq!(summary.len(), 2, "{summary:?}");
+    assert_eq!(summary[0]["kind"], "added");
+    assert_eq!(summary[0]["item"]["pack"], "cats");
+    assert_eq!(summary[1], json!({"kind": "removed", "path": root.join("anime/a.jpg")}));
+
+    fs::write(root.join("cats/b.png"), b"bb").unwrap();
+    let changes = tracker.apply(vec![root.join("cats/b.png")]);
+    assert!(matches!(changes.as_slice(), [library::LibraryChange::Modified { .. }]));
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn index_rereads_only_changed_directories() {
+    let root = scratch_dir("index");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a/one.jpg"), b"1").unwrap();
+    let library = one_root_library(&root);
+    let mut index = index::LibraryIndex::default();
+
+    assert!(index.refresh(&library));
+    assert!(!index.refresh(&library));
+
+    fs::write(root.join("a/two.png"), b"2").unwrap();
+    assert!(index.refresh(&library));
+    let mut names: Vec<_> = index.items(&library).into_iter().map(|i| (i.pack, i.file_name)).collect();
+    names.sort();
+    assert_eq!(names, [("a".to_string(), "one.jpg".to_string()), ("a".to_string(), "two.png".to_string())]);
+
+    fs::remove_dir_all(root.join("a")).unwrap();
+    assert!(index.refresh(&library));
+    assert!(index.items(&library).is_empty());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn index_rereads_headers_of_files_rewritten_in_unchanged_directories() {
+    let root = scratch_dir("index-restat");
+    image::RgbImage::new(4, 2).save(root.join("w.png")).unwrap();
+    // Old enough that the directory is trusted rather than re-read as racy.
+    let hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    fs::File::open(&root).unwrap().set_modified(hour_ago).unwrap();
+    let library = one_root_library(&root);
+    let mut index = index::LibraryIndex::default();
+    assert!(index.refresh(&library));
+    assert!(!index.refresh(&library));
+
+    image::RgbImage::new(40, 20).save(root.join("w.png")).unwrap();
+    fs::File::open(&root).unwrap().set_modified(hour_ago).unwrap();
+    assert!(index.refresh(&library));
+    assert_eq!(index.items(&library)[0].width, Some(40));
+
+    fs::remove_file(root.join("w.png")).unwrap();
+    fs::File::open(&root).unwrap().set_modified(hour_ago).unwrap();
+    assert!(index.refresh(&library));
+    assert!(index.items(&library).is_empty());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn live_index_serves_from_memory_and_rereads_only_marked_directories() {
+    let root = scratch_dir("index-live");
+    fs::write(root.join("a.jpg"), b"a").unwrap();
+    let library = one_root_library(&root);
+    let shared = index::SharedIndex::in_memory();
+    let names = |items: Vec<library::WallpaperItem>| {
+        let mut names: Vec<_> = items.into_iter().map(|i| i.file_name).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(shared.list(&library).unwrap()), ["a.jpg"]);
+    shared.go_live(&library);
+
+    // Unmarked changes are not looked for.
+    fs::write(root.join("b.png"), b"b").unwrap();
+    assert_eq!(names(shared.list(&library).unwrap()), ["a.jpg"]);
+    shared.mark_dirty(&[root.join("b.png")]);
+    assert_eq!(names(shared.list(&library).unwrap()), ["a.jpg", "b.png"]);
+
+    fs::create_dir_all(root.join("new/deeper")).unwrap();
+    fs::write(root.join("new/deeper/c.jpg"), b"c").unwrap();
+    shared.mark_dirty(&[root.join("new")]);
+    assert_eq!(names(shared.list(&library).unwrap()), ["a.jpg", "b.png", "c.jpg"]);
+
+    fs::remove_dir_all(root.join("new")).unwrap();
+    shared.mark_dirty(&[root.join("new")]);
+    assert_eq!(names(shared.list(&library).unwrap()), ["a.jpg", "b.png"]);
+
+    fs::remove_file(root.join("a.jpg")).unwrap();
+    shared.stop_live();
+    assert_eq!(names(shared.list(&library).unwrap()), ["b.png"]);
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn scans_skip_hidden_excluded_and_too_deep_paths_and_survive_loops() {
+    let root = scratch_dir("scan-rules");
+    for dir in ["a/b/c", ".hidden", "raw"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in ["top.jpg", "a/one.jpg", "a/b/two.jpg", "a/b/c/three.jpg", ".hidden/h.jpg", "raw/r.jpg", "a/skip.tmp.png"] {
+        fs::write(root.join(file), b"x").unwrap();
+    }
+    std::os::unix::fs::symlink(&root, root.join("a/loop")).unwrap();
+    let mut library = one_root_library(&root);
+    library.max_depth = 2;
+    library.roots[0].exclude = library::Excludes::new(vec!["raw/**".into(), "**/*.tmp.*".into()]).unwrap();
+
+    let mut index = index::LibraryIndex::default();
+    index.refresh(&library);
+    let mut names: Vec<_> = index.items(&library).into_iter().map(|item| item.file_name).collect();
+    names.sort();
+    assert_eq!(names, ["one.jpg", "top.jpg", "two.jpg"]);
+    assert!(index.unreadable().is_empty());
+    assert!(library.item(&root.join(".hidden/h.jpg")).is_none());
+
+    // Changed rules re-read what was already indexed.
+    library.include_hidden = true;
+    assert!(index.refresh(&library));
+    assert_eq!(index.items(&library).len(), 4);
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn thumbnails_are_downscaled_and_shared_by_identical_files() {
+    let root = scratch_dir("thumbs");
+    let cache = root.join("cache");
+    image::RgbImage::new(1000, 500).save(root.join("wide.png")).unwrap();
+    fs::copy(root.join("wide.png"), root.join("copy.png")).unwrap();
+
+    let hash = thumbs::generate(&root.join("wide.png"), &cache).unwrap();
+    assert_eq!(thumbs::generate(&root.join("copy.png"), &cache).unwrap(), hash);
+    let thumb = image::open(cache.join(format!("{hash}.jpg"))).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (thumbs::THUMB_EDGE, thumbs::THUMB_EDGE / 2));
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 1);
+
+    fs::write(root.join("broken.jpg"), b"not an image").unwrap();
+    assert!(thumbs::generate(&root.join("broken.jpg"), &cache).is_err());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn media_ranges_follow_rfc_9110() {
+    use media::{parse_range, ByteRange};
+    assert_eq!(parse_range(None, 10), ByteRange::Full);
+    assert_eq!(parse_range(Some("bytes=2-5"), 10), ByteRange::Partial { start: 2, end: 5 });
+    assert_eq!(parse_range(Some("bytes=4-"), 10), ByteRange::Partial { start: 4, end: 9 });
+    assert_eq!(parse_range(Some("bytes=-3"), 10), ByteRange::Partial { start: 7, end: 9 });
+    assert_eq!(parse_range(Some("bytes=8-100"), 10), ByteRange::Partial { start: 8, end: 9 });
+    assert_eq!(parse_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+    assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+    assert_eq!(parse_range(Some("bytes=5-2"), 10), ByteRange::Full);
+}
+
+#[test]
+fn media_scheme_serves_only_files_under_the_roots() {
+    use tauri::http::{header, Request, StatusCode};
+    let root = scratch_dir("media");
+    fs::create_dir_all(root.join("walls")).unwrap();
+    fs::write(root.join("walls/clip.mp4"), b"0123456789").unwrap();
+    fs::write(root.join("secret.txt"), b"no").unwrap();
+    let roots = vec![root.join("walls")];
+    let get = |path: &Path, range: Option<&str>| {
+        let encoded = percent_encoding::utf8_percent_encode(path.to_str().unwrap(), percent_encoding::NON_ALPHANUMERIC);
+        let mut request = Request::builder().uri(format!("kitowall://localhost/{encoded}"));
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        media::serve(&roots, &request.body(Vec::new()).unwrap())
+    };
+
+    let full = get(&root.join("walls/clip.mp4"), None);
+    assert_eq!(full.status(), StatusCode::OK);
+    assert_eq!(full.headers()[header::CONTENT_TYPE], "video/mp4");
+    assert_eq!(full.body(), b"0123456789");
+
+    let part = get(&root.join("walls/clip.mp4"), Some("bytes=2-5"));
+    assert_eq!(part.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(part.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+    assert_eq!(part.body(), b"2345");
+
+    // Big clips are never read whole: a plain GET gets the first chunk.
+    let big = vec![7u8; 5 * 1024 * 1024];
+    fs::write(root.join("walls/big.webm"), &big).unwrap();
+    let first = get(&root.join("walls/big.webm"), None);
+    assert_eq!(first.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(first.headers()[header::CONTENT_RANGE], format!("bytes 0-4194303/{}", big.len()));
+    assert_eq!(first.body().len(), 4 * 1024 * 1024);
+    assert!(!first.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    assert_eq!(get(&root.join("secret.txt"), None).status(), StatusCode::FORBIDDEN);
+    assert_eq!(get(&root.join("walls/../secret.txt"), None).status(), StatusCode::FORBIDDEN);
+    assert_eq!(get(&root.join("walls/missing.png"), None).status(), StatusCode::NOT_FOUND);
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn image_metadata_comes_from_headers_and_the_thumbnail() {
+    let root = scratch_dir("metadata");
+    let mut image = image::RgbImage::from_pixel(300, 200, image::Rgb([255, 0, 0]));
+    image.put_pixel(0, 0, image::Rgb([0, 0, 255]));
+    // Named .jpg but PNG inside: the format comes from the magic bytes.
+    image.save_with_format(root.join("red.jpg"), image::ImageFormat::Png).unwrap();
+
+    let header = library::read_header(&root.join("red.jpg")).unwrap();
+    assert_eq!((header.width, header.height, header.format.as_str()), (300, 200, "png"));
+    assert_eq!(thumbs::dominant_color(&image).as_deref(), Some("#ff0000"));
+
+    let library = one_root_library(&root);
+    let item = library.item(&root.join("red.jpg")).unwrap();
+    assert_eq!(item.aspect, Some(1.5));
+    assert_eq!(item.size, fs::metadata(root.join("red.jpg")).unwrap().len());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn duplicates_group_rescaled_copies_and_keep_the_largest() {
+    let root = scratch_dir("dupes");
+    let big = image::RgbImage::from_fn(400, 200, |x, y| image::Rgb([(x * 255 / 400) as u8, (y * 255 / 200) as u8, 96]));
+    image::imageops::resize(&big, 200, 100, image::imageops::FilterType::Triangle)
+        .save(root.join("small.png"))
+        .unwrap();
+    big.save(root.join("big.png")).unwrap();
+    image::imageops::flip_horizontal(&big).save(root.join("other.png")).unwrap();
+    let library = one_root_library(&root);
+    let items: Vec<_> = ["small.png", "big.png", "other.png"]
+        .iter()
+        .map(|name| {
+            let item = library.item(&root.join(name)).unwrap();
+            let hash = dupes::dhash(&image::open(&item.path).unwrap());
+            (item, hash)
+        })
+        .collect();
+
+    let groups = dupes::group(items, dupes::DEFAULT_MAX_DISTANCE);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].items.len(), 2);
+    assert!(groups[0].keep.ends_with("big.png"));
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn library_classifies_images_animations_and_videos() {
+    use library::MediaKind::{Animated, Image, Video};
+    let root = scratch_dir("kinds");
+    image::RgbImage::new(4, 4).save(root.join("still.png")).unwrap();
+    let mut gif = image::codecs::gif::GifEncoder::new(fs::File::create(root.join("loop.gif")).unwrap());
+    gif.set_repeat(image::codecs::gif::Repeat::Infinite).unwrap();
+    for shade in [0u8, 255] {
+        let frame = image::RgbaImage::from_pixel(4, 4, image::Rgba([shade, shade, shade, 255]));
+        gif.encode_frame(image::Frame::new(frame)).unwrap();
+    }
+    drop(gif);
+    fs::write(root.join("clip.webm"), b"not decoded").unwrap();
+    fs::write(root.join("notes.txt"), b"x").unwrap();
+    let library = one_root_library(&root);
+    let mut index = index::LibraryIndex::default();
+    index.refresh(&library);
+
+    let kinds: std::collections::BTreeMap<_, _> =
+        index.items(&library).into_iter().map(|item| (item.file_name, item.kind)).collect();
+    let expected = [("clip.webm", Video), ("loop.gif", Animated), ("still.png", Image)];
+    assert_eq!(kinds, expected.map(|(name, kind)| (name.to_string(), kind)).into());
+
+    let query = library::LibraryQuery {
+        kind: Some(Video),
+        ..Default::default()
+    };
+    let (total, page) = query.apply(index.items(&library), &HashSet::new());
+    assert_eq!((total, page[0].file_name.as_str()), (1, "clip.webm"));
+    let _ = fs::remove_dir_all(root);
+}
+
+fn wallpaper(name: &str, pack: &str, modified_ms: u64, size: (u32, u32)) -> library::WallpaperItem {
+    library::WallpaperItem {
+        path: format!("/walls/{pack}/{name}"),
+        pack: pack.to_string(),
+        file_name: name.to_string(),
+        kind: library::MediaKind::Image,
+        modified_ms,
+        size: u64::from(size.0 * size.1),
+        width: Some(size.0),
+        height: Some(size.1),
+        aspect: Some(f64::from(size.0) / f64::from(size.1)),
+        format: Some("png".to_string()),
+        dominant_color: None,
+        thumb: None,
+    }
+}
+
+#[test]
+fn library_query_filters_sorts_and_pages() {
+    let items = vec![
+        wallpaper("city.png", "anime", 30, (3440, 1440)),
+        wallpaper("Tall.png", "anime", 20, (1080, 1920)),
+        wallpaper("forest.png", "nature", 10, (3840, 2160)),
+        wallpaper("small.png", "nature", 40, (1280, 720)),
+    ];
+    let names = |query: library::LibraryQuery| {
+        let (total, page) = query.apply(items.clone(), &HashSet::new());
+        (total, page.into_iter().map(|i| i.file_name).collect::<Vec<_>>())
+    };
+    let query = |json: serde_json::Value| serde_json::from_value::<library::LibraryQuery>(json).unwrap();
+
+    assert_eq!(names(query(json!({}))).1, ["small.png", "city.png", "Tall.png", "forest.png"]);
+    assert_eq!(
+        names(query(json!({"minWidth": 3440, "minHeight": 1440}))).1,
+        ["city.png", "forest.png"]
+    );
+    assert_eq!(names(query(json!({"orientation": "portrait"}))).1, ["Tall.png"]);
+    assert_eq!(names(query(json!({"pack": "nature", "sort": "size"}))).1, ["forest.png", "small.png"]);
+    assert_eq!(names(query(json!({"q": "TALL"}))).1, ["Tall.png"]);
+    assert_eq!(names(query(json!({"modifiedAfter": 20, "modifiedBefore": 40}))).1, ["city.png", "Tall.png"]);
+    assert_eq!(
+        names(query(json!({"sort": "name", "offset": 1, "limit": 2}))),
+        (4, vec!["forest.png".to_string(), "small.png".to_string()])
+    );
+}
@@ -0,0 +1,422 @@
+// Downscaled previews for the wallpaper grid. Thumbnails are content-addressed:
+// each is named after the BLAKE3 hash of the source bytes, so copies of an image
+// share one file and a renamed image keeps its thumbnail. A manifest maps source
+// paths (with the mtime they were hashed at) to hashes so a listing never reads
+// image data. Missing or stale thumbnails are made on a background thread; until
+// one lands the UI shows the original. The dominant color and the perceptual
+// hash are taken from the thumbnail while it is at hand and kept in the
+// manifest too.
+//
+// Videos get a poster frame instead, grabbed with the host's ffmpeg. Hashing a
+// whole clip would be too slow, so a poster is named after the hash of the
+// clip's path, size and mtime.
+//
+// AVIF decoding needs the system dav1d library, which this build doesn't link,
+// so AVIF images are decoded by ffmpeg too. Without it they get no thumbnail and
+// the webview decodes the original instead.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+
+use crate::error::UiError;
+use crate::library::{self, WallpaperItem};
+
+/// Bump when the thumbnail size or format changes; older manifests are discarded.
+const MANIFEST_VERSION: u32 = 3;
+const MANIFEST_FILE: &str = "manifest.json";
+/// Longest edge of a thumbnail, enough for the largest grid card on HiDPI.
+pub const THUMB_EDGE: u32 = 480;
+const THUMB_QUALITY: u8 = 82;
+/// How long a failed source is left alone before it is tried again; a missing
+/// tool (ffmpeg for videos and AVIF) may have been installed meanwhile.
+const RETRY_FAILED_MS: u64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThumbRecord {
+    modified_ms: u64,
+    /// `None` when the image could not be decoded; retried once it changes or
+    /// `RETRY_FAILED_MS` after `failed_at`.
+    hash: Option<String>,
+    color: Option<String>,
+    /// See `dupes::dhash`.
+    dhash: Option<u64>,
+    /// When generation last failed, in ms since the epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed_at: Option<u64>,
+}
+
+impl ThumbRecord {
+    /// Whether this record still stands for a source modified at `modified_ms`.
+    fn is_current(&self, modified_ms: u64, now_ms: u64) -> bool {
+        self.modified_ms == modified_ms
+            && (self.hash.is_some() || self.failed_at.is_some_and(|at| now_ms.saturating_sub(at) < RETRY_FAILED_MS))
+    }
+}
+
+fn now_ms() -> u64 {
+    library::since_epoch(std::time::SystemTime::now()).as_millis() as u64
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    sources: BTreeMap<PathBuf, ThumbRecord>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl Manifest {
+    fn load(dir: &Path) -> Self {
+        fs::read(dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<Self>(&raw).ok())
+            .filter(|manifest| manifest.version == MANIFEST_VERSION)
+            .unwrap_or_default()
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), UiError> {
+        fs::create_dir_all(dir)?;
+        let tmp = dir.join(format!("{MANIFEST_FILE}.tmp"));
+        fs::write(&tmp, serde_json::to_vec(self).map_err(|e| UiError::Host(e.to_string()))?)?;
+        fs::rename(&tmp, dir.join(MANIFEST_FILE))?;
+        Ok(())
+    }
+}
+
+pub fn thumbs_dir() -> Result<PathBuf, UiError> {
+    Ok(crate::paths::cache_dir()?.join("thumbs"))
+}
+
+fn thumb_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(format!("{hash}.jpg"))
+}
+
+fn can_decode(path: &Path) -> bool {
+    library::is_image_ext(path) || library::is_video_ext(path)
+}
+
+fn is_avif(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("avif"))
+}
+
+/// `bytes` of the image `source`, decoded; AVIF goes through ffmpeg.
+fn decode(source: &Path, bytes: &[u8]) -> Result<DynamicImage, UiError> {
+    let decoded = if is_avif(source) {
+        image::load_from_memory(&grab_frame(source, None)?)
+    } else {
+        image::load_from_memory(bytes)
+    };
+    decoded.map_err(|e| UiError::Host(format!("{}: {e}", source.display())))
+}
+
+/// Seconds into a clip the poster is taken from, past most fade-ins; clips
+/// shorter than that get their first frame.
+const POSTER_SEEK: &str = "1";
+
+/// Writes the thumbnail for `source` into `dir` unless one for the same content
+/// is already there, and returns the content hash.
+pub fn generate(source: &Path, dir: &Path) -> Result<String, UiError> {
+    if library::is_video_ext(source) {
+        return generate_poster(source, dir);
+    }
+    let bytes = fs::read(source)?;
+    let hash = blake3::hash(&bytes).to_hex().to_string();
+    if thumb_path(dir, &hash).exists() {
+        return Ok(hash);
+    }
+    write_thumbnail(decode(source, &bytes)?, source, dir, &hash)?;
+    Ok(hash)
+}
+
+/// Writes a poster frame for the video `source` unless it is already there,
+/// and returns its hash.
+fn generate_poster(source: &Path, dir: &Path) -> Result<String, UiError> {
+    let meta = fs::metadata(source)?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(source.as_os_str().as_encoded_bytes());
+    hasher.update(&meta.len().to_le_bytes());
+    hasher.update(&library::modified_ms(source).to_le_bytes());
+    let hash = hasher.finalize().to_hex().to_string();
+    if thumb_path(dir, &hash).exists() {
+        return Ok(hash);
+    }
+    let mut frame = vec![];
+    for seek in [Some(POSTER_SEEK), None] {
+        frame = grab_frame(source, seek)?;
+        if !frame.is_empty() {
+            break;
+        }
+    }
+    let image = image::load_from_memory(&frame).map_err(|e| UiError::Host(format!("{}: {e}", source.display())))?;
+    write_thumbnail(image, source, dir, &hash)?;
+    Ok(hash)
+}
+
+/// One frame of `source` (a clip or an AVIF image) as JPEG bytes from ffmpeg;
+/// empty when `seek` is past the end.
+fn grab_frame(source: &Path, seek: Option<&str>) -> Result<Vec<u8>, UiError> {
+    let mut cmd = crate::host_aware_command("ffmpeg");
+    cmd.args(["-v", "error", "-nostdin"]);
+    if let Some(seek) = seek {
+        cmd.args(["-ss", seek]);
+    }
+    cmd.arg("-i")
+        .arg(source)
+        .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "mjpeg", "-"]);
+    let out = cmd
+        .output()
+        .map_err(|e| UiError::Host(format!("failed to run ffmpeg: {e}")))?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr);
+        return Err(UiError::Host(format!("ffmpeg failed on {}: {}", source.display(), err.trim())));
+    }
+    Ok(out.stdout)
+}
+
+/// Shrinks `image` to fit `THUMB_EDGE` and saves it as `<hash>.jpg` in `dir`.
+fn write_thumbnail(mut image: DynamicImage, source: &Path, dir: &Path, hash: &str) -> Result<(), UiError> {
+    let encode_error = |e: image::ImageError| UiError::Host(format!("{}: {e}", source.display()));
+    if image.width() > THUMB_EDGE || image.height() > THUMB_EDGE {
+        image = image.thumbnail(THUMB_EDGE, THUMB_EDGE);
+    }
+    fs::create_dir_all(dir)?;
+    let tmp = dir.join(format!("{hash}.tmp"));
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    JpegEncoder::new_with_quality(&mut out, THUMB_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(encode_error)?;
+    out.flush()?;
+    drop(out);
+    fs::rename(&tmp, thumb_path(dir, hash))?;
+    Ok(())
+}
+
+/// The most common color as `#rrggbb`: pixels are bucketed at 4 bits per
+/// channel and the fullest bucket is averaged.
+pub fn dominant_color(image: &RgbImage) -> Option<String> {
+    let mut buckets = vec![(0u64, [0u64; 3]); 1 << 12];
+    for pixel in image.pixels() {
+        let [r, g, b] = pixel.0;
+        let bucket = &mut buckets[usize::from(r >> 4) << 8 | usize::from(g >> 4) << 4 | usize::from(b >> 4)];
+        bucket.0 += 1;
+        for (sum, channel) in bucket.1.iter_mut().zip([r, g, b]) {
+            *sum += u64::from(channel);
+        }
+    }
+    let (count, [r, g, b]) = buckets.into_iter().max_by_key(|(count, _)| *count)?;
+    (count > 0).then(|| format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count))
+}
+
+/// Dominant color of `path`, decoding it in full; for images that have no
+/// thumbnail yet.
+pub fn color_of(path: &Path) -> Option<String> {
+    let image = decode(path, &fs::read(path).ok()?).ok()?.thumbnail(64, 64);
+    dominant_color(&image.to_rgb8())
+}
+
+#[derive(Default)]
+struct Shared {
+    /// Loaded on first use.
+    manifest: Option<Manifest>,
+    /// Sources queued and not yet done, so a listing doesn't queue them twice.
+    pending: HashSet<PathBuf>,
+}
+
+impl Shared {
+    fn manifest(&mut self, dir: &Path) -> &mut Manifest {
+        self.manifest.get_or_insert_with(|| Manifest::load(dir))
+    }
+}
+
+/// The thumbnail cache and its worker, shared by the listing command and the
+/// library watcher.
+#[derive(Default)]
+pub struct Thumbnails {
+    shared: Arc<Mutex<Shared>>,
+    queue: Mutex<Option<mpsc::Sender<PathBuf>>>,
+}
+
+impl Thumbnails {
+    /// Starts the background worker. `on_ready(source, thumb, color)` runs as
+    /// each thumbnail lands.
+    pub fn start(&self, on_ready: impl Fn(&Path, &Path, Option<&str>) + Send + 'static) -> Result<(), UiError> {
+        let dir = thumbs_dir()?;
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let shared = Arc::clone(&self.shared);
+        std::thread::Builder::new()
+            .name("kitowall-thumbs".to_string())
+            .spawn(move || {
+                while let Ok(first) = rx.recv() {
+                    // Everything queued meanwhile goes in the same batch, so the
+                    // manifest is written once per burst.
+                    let batch: Vec<PathBuf> = std::iter::once(first).chain(rx.try_iter()).collect();
+                    for source in batch {
+                        if let Some((thumb, color)) = make(&shared, &dir, &source) {
+                            on_ready(&source, &thumb, color.as_deref());
+                        }
+                    }
+                    if let Ok(mut shared) = shared.lock() {
+                        if let Err(err) = shared.manifest(&dir).save(&dir) {
+                            eprintln!("[kitowall-ui] could not save the thumbnail manifest: {err}");
+                        }
+                        collect_garbage(shared.manifest(&dir), &dir);
+                    }
+                }
+            })?;
+        if let Ok(mut queue) = self.queue.lock() {
+            *queue = Some(tx);
+        }
+        Ok(())
+    }
+
+    /// Forgets every source but `items`, the whole library.
+    pub fn retain(&self, items: &[WallpaperItem]) {
+        let Ok(dir) = thumbs_dir() else {
+            return;
+        };
+        let listed: HashSet<&str> = items.iter().map(|item| item.path.as_str()).collect();
+        if let Ok(mut shared) = self.shared.lock() {
+            shared
+                .manifest(&dir)
+                .sources
+                .retain(|path, _| path.to_str().is_some_and(|p| listed.contains(p)));
+        }
+    }
+
+    /// Sets `thumb` on the items whose thumbnail is current and queues the rest.
+    pub fn attach<'a>(&self, items: impl IntoIterator<Item = &'a mut WallpaperItem>) {
+        let Ok(dir) = thumbs_dir() else {
+            return;
+        };
+        let Some(queue) = self.queue.lock().ok().and_then(|q| q.clone()) else {
+            return;
+        };
+        let Ok(mut shared) = self.shared.lock() else {
+            return;
+        };
+        let mut queued = vec![];
+        let now = now_ms();
+        for item in items {
+            let path = PathBuf::from(&item.path);
+            match shared.manifest(&dir).sources.get(&path) {
+                Some(record) if record.is_current(item.modified_ms, now) => {
+                    item.dominant_color = record.color.clone();
+                    let Some(hash) = &record.hash else {
+                        continue;
+                    };
+                    let thumb = thumb_path(&dir, hash);
+                    if thumb.exists() {
+                        item.thumb = Some(thumb.to_string_lossy().to_string());
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            if can_decode(&path) && shared.pending.insert(path.clone()) {
+                queued.push(path);
+            }
+        }
+        drop(shared);
+        for path in queued {
+            let _ = queue.send(path);
+        }
+    }
+
+    /// Perceptual hashes for `items`, in order. Thumbnails still missing are
+    /// made on the calling thread; `None` for images that can't be decoded.
+    pub fn dhashes(&self, items: &[WallpaperItem]) -> Vec<Option<u64>> {
+        let Ok(dir) = thumbs_dir() else {
+            return vec![None; items.len()];
+        };
+        let cached = |shared: &Mutex<Shared>, item: &WallpaperItem| {
+            let mut shared = shared.lock().ok()?;
+            let record = shared.manifest(&dir).sources.get(Path::new(&item.path))?;
+            record.is_current(item.modified_ms, now_ms()).then_some(record.dhash)
+        };
+        let mut made = false;
+        let hashes = items
+            .iter()
+            .map(|item| {
+                if let Some(known) = cached(&self.shared, item) {
+                    return known;
+                }
+                let path = Path::new(&item.path);
+                if !can_decode(path) {
+                    return None;
+                }
+                made = true;
+                make(&self.shared, &dir, path)?;
+                cached(&self.shared, item).flatten()
+            })
+            .collect();
+        if made {
+            if let Ok(mut shared) = self.shared.lock() {
+                if let Err(err) = shared.manifest(&dir).save(&dir) {
+                    eprintln!("[kitowall-ui] could not save the thumbnail manifest: {err}");
+                }
+            }
+        }
+        hashes
+    }
+}
+
+/// Makes one thumbnail and records it. Returns its path and the dominant
+/// color, or `None` if the source could not be read or decoded.
+fn make(shared: &Mutex<Shared>, dir: &Path, source: &Path) -> Option<(PathBuf, Option<String>)> {
+    let modified_ms = library::modified_ms(source);
+    let hash = match generate(source, dir) {
+        Ok(hash) => Some(hash),
+        Err(err) => {
+            eprintln!("[kitowall-ui] no thumbnail for {}: {err}", source.display());
+            None
+        }
+    };
+    let thumb = hash.as_ref().map(|hash| thumb_path(dir, hash));
+    let decoded = thumb.as_ref().and_then(|thumb| image::open(thumb).ok());
+    let color = decoded.as_ref().and_then(|image| dominant_color(&image.to_rgb8()));
+    let dhash = decoded.as_ref().map(crate::dupes::dhash);
+    let mut shared = shared.lock().ok()?;
+    shared.pending.remove(source);
+    // A source removed meanwhile would otherwise linger until the next listing.
+    if source.exists() {
+        let record = ThumbRecord {
+            modified_ms,
+            failed_at: hash.is_none().then(now_ms),
+            hash,
+            color: color.clone(),
+            dhash,
+        };
+        shared.manifest(dir).sources.insert(source.to_path_buf(), record);
+    }
+    thumb.map(|thumb| (thumb, color))
+}
+
+/// Deletes thumbnails no source points at any more.
+fn collect_garbage(manifest: &Manifest, dir: &Path) {
+    let live: HashSet<&str> = manifest.sources.values().filter_map(|r| r.hash.as_deref()).collect();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let orphan = path.extension().is_some_and(|e| e == "jpg")
+            && path.file_stem().and_then(|s| s.to_str()).is_some_and(|stem| !live.contains(stem));
+        if orphan {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
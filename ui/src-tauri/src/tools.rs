@@ -0,0 +1,123 @@
+use serde::Serialize;
+use std::sync::RwLock;
+
+/// Oldest CLI release this UI talks to; newer majors are refused as well.
+/// 3.6.0 added `--secrets-stdin`, which every key and Steam Guard call uses, and
+/// `live apply-file`, which video wallpapers go through.
+pub const MIN_CLI_VERSION: (u64, u64, u64) = (3, 6, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compat {
+    Unknown,
+    Ok,
+    Warn,
+    Incompatible,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Handshake {
+    pub cli_version: Option<String>,
+    pub ui_version: &'static str,
+    pub status: Compat,
+    pub message: Option<String>,
+}
+
+impl Handshake {
+    fn pending() -> Self {
+        Self {
+            cli_version: None,
+            ui_version: env!("CARGO_PKG_VERSION"),
+            status: Compat::Unknown,
+            message: None,
+        }
+    }
+
+    pub fn unavailable(message: String) -> Self {
+        Self {
+            message: Some(message),
+            ..Self::pending()
+        }
+    }
+
+    /// Classifies the raw `kitowall --version` output.
+    pub fn from_version_output(raw: &str) -> Self {
+        let version = raw.trim().trim_start_matches('v').to_string();
+        let min = format!("{}.{}.{}", MIN_CLI_VERSION.0, MIN_CLI_VERSION.1, MIN_CLI_VERSION.2);
+        let (status, message) = match parse_version(&version) {
+            None => (
+                Compat::Warn,
+                Some(format!("could not parse kitowall version {version:?}; expected >= {min}")),
+            ),
+            Some(v) if v.0 != MIN_CLI_VERSION.0 || v < MIN_CLI_VERSION => (
+                Compat::Incompatible,
+                Some(format!(
+                    "kitowall {version} is not supported by kitowall-ui {}; install kitowall {}.x >= {min}",
+                    env!("CARGO_PKG_VERSION"),
+                    MIN_CLI_VERSION.0
+                )),
+            ),
+            Some(_) => (Compat::Ok, None),
+        };
+        Self {
+            cli_version: Some(version),
+            ui_version: env!("CARGO_PKG_VERSION"),
+            status,
+            message,
+        }
+    }
+}
+
+fn parse_version(raw: &str) -> Option<(u64, u64, u64)> {
+    let core = raw.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedTools {
+    pub kitowall: Vec<String>,
+    pub kitsune: Vec<String>,
+    pub handshake: Handshake,
+}
+
+/// Resolved kitowall/kitsune command lines. Resolution shells out to the host,
+/// so it runs once and is only repeated through `store` after a refresh.
+#[derive(Default)]
+pub struct ToolCache {
+    inner: RwLock<Option<ResolvedTools>>,
+}
+
+impl ToolCache {
+    /// The cached tools. Before the startup refresh has stored them, they are
+    /// resolved on the blocking pool, without the version handshake.
+    pub async fn current(&self) -> ResolvedTools {
+        if let Some(tools) = self.inner.read().ok().and_then(|g| g.clone()) {
+            return tools;
+        }
+        let (kitowall, kitsune) =
+            tauri::async_runtime::spawn_blocking(|| (crate::resolve_kitowall_cmd(), crate::resolve_kitsune_cmd()))
+                .await
+                .unwrap_or_else(|_| (vec!["kitowall".to_string()], vec!["kitsune".to_string()]));
+        let resolved = ResolvedTools {
+            kitowall,
+            kitsune,
+            handshake: Handshake::pending(),
+        };
+        if let Ok(mut guard) = self.inner.write() {
+            guard.get_or_insert_with(|| resolved.clone());
+        }
+        resolved
+    }
+
+    pub fn store(&self, tools: ResolvedTools) {
+        if let Ok(mut guard) = self.inner.write() {
+            *guard = Some(tools);
+        }
+    }
+}
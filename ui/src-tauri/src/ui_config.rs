@@ -0,0 +1,138 @@
+// UI-side settings in ~/.config/kitowall/ui.toml. Kept apart from config.json,
+// which belongs to the CLI.
+//
+//   [tools]
+//   kitowall = "node '/opt/my apps/kitowall/dist/cli.js'"
+//   mpv = "/usr/bin/mpv --vo=gpu"
+//
+//   [library]
+//   max_depth = 8
+//   include_hidden = false
+//   [library.exclude]
+//   "~/Pictures/Wallpapers" = ["tmp/**", "**/*.part"]
+//   my-local-pack = ["drafts/**"]
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::UiError;
+
+/// Host binaries whose command line can be overridden.
+pub const OVERRIDABLE_TOOLS: [&str; 7] = ["kitowall", "kitsune", "mpv", "swww", "systemctl", "xdg-open", "ffmpeg"];
+
+/// Directory levels scanned below each library root by default.
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// How the wallpaper library is scanned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibrarySettings {
+    pub max_depth: usize,
+    /// Dot files and folders are skipped unless set.
+    pub include_hidden: bool,
+    /// Globs relative to a root, keyed by the root's path or local pack name.
+    pub exclude: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for LibrarySettings {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            include_hidden: false,
+            exclude: BTreeMap::new(),
+        }
+    }
+}
+
+impl LibrarySettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiConfig {
+    #[serde(default)]
+    pub tools: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "LibrarySettings::is_default")]
+    pub library: LibrarySettings,
+}
+
+pub fn config_path() -> Result<PathBuf, UiError> {
+    crate::paths::ui_config_file()
+}
+
+pub fn load() -> Result<UiConfig, UiError> {
+    let path = config_path()?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(UiConfig::default()),
+        Err(e) => return Err(e.into()),
+    };
+    toml::from_str(&raw).map_err(|e| UiError::BadConfig(format!("{}: {e}", path.display())))
+}
+
+pub fn save(config: &UiConfig) -> Result<(), UiError> {
+    let path = config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let raw = toml::to_string_pretty(config).map_err(|e| UiError::Host(e.to_string()))?;
+    fs::write(&path, raw)?;
+    Ok(())
+}
+
+/// Splits a configured command line with shell quoting rules.
+pub fn parse_command_line(tool: &str, cmdline: &str) -> Result<Vec<String>, UiError> {
+    let parts = shell_words::split(cmdline)
+        .map_err(|e| UiError::invalid(format!("invalid command line for {tool}: {e}")))?;
+    if parts.is_empty() {
+        return Err(UiError::invalid(format!("command line for {tool} is empty")));
+    }
+    Ok(parts)
+}
+
+/// Configured command line for `tool`, if any. A broken ui.toml is logged to
+/// stderr and otherwise treated as "no override".
+pub fn tool_override(tool: &str) -> Option<Vec<String>> {
+    let config = match load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[kitowall-ui] ignoring ui.toml: {e}");
+            return None;
+        }
+    };
+    let cmdline = config.tools.get(tool)?;
+    match parse_command_line(tool, cmdline) {
+        Ok(parts) => Some(parts),
+        Err(e) => {
+            eprintln!("[kitowall-ui] ignoring ui.toml override: {e}");
+            None
+        }
+    }
+}
+
+/// The program `tool` ends up running once ui.toml overrides are followed: an
+/// override naming another overridden tool leads on to that tool's override.
+/// `lookup` is `tool_override`, or a stand-in in tests. A chain that comes
+/// back to a tool it already passed is a `BadConfig`.
+pub fn overridden_program(tool: &str, lookup: impl Fn(&str) -> Option<Vec<String>>) -> Result<String, UiError> {
+    let mut chain = vec![tool.to_string()];
+    loop {
+        let current = &chain[chain.len() - 1];
+        let Some(parts) = lookup(current) else {
+            return Ok(current.clone());
+        };
+        let program = parts[0].clone();
+        if program == *current || std::path::Path::new(&program).is_absolute() {
+            return Ok(program);
+        }
+        let looped = chain.contains(&program);
+        chain.push(program);
+        if looped {
+            return Err(UiError::BadConfig(format!("ui.toml [tools] overrides loop: {}", chain.join(" -> "))));
+        }
+    }
+}
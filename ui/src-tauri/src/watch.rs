@@ -0,0 +1,52 @@
+// Debounced filesystem watching on top of `notify`. Each watcher gets its own
+// thread and hands the callback every path touched during one burst of events.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::UiError;
+
+/// Stops watching when dropped.
+pub struct DirWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+pub fn watch<F>(dirs: &[(PathBuf, RecursiveMode)], debounce: Duration, on_change: F) -> Result<DirWatcher, UiError>
+where
+    F: Fn(Vec<PathBuf>) + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| UiError::Host(format!("file watcher: {e}")))?;
+    for (dir, mode) in dirs {
+        watcher
+            .watch(dir, *mode)
+            .map_err(|e| UiError::Host(format!("cannot watch {}: {e}", dir.display())))?;
+    }
+
+    thread::spawn(move || {
+        // Ends once the watcher (and with it the sender) is dropped.
+        while let Ok(first) = rx.recv() {
+            let mut touched = BTreeSet::new();
+            collect(&mut touched, first);
+            while let Ok(more) = rx.recv_timeout(debounce) {
+                collect(&mut touched, more);
+            }
+            if !touched.is_empty() {
+                on_change(touched.into_iter().collect());
+            }
+        }
+    });
+    Ok(DirWatcher { _watcher: watcher })
+}
+
+fn collect(into: &mut BTreeSet<PathBuf>, event: notify::Result<Event>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => into.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => eprintln!("[kitowall-ui] file watcher: {e}"),
+    }
+}